@@ -1,27 +1,39 @@
 use std::time::{Duration, Instant};
 
-use crate::data::{self, Snapshot};
+use crate::config::Config;
+use crate::data::Sources;
+use crate::segment::{Registry, Snapshot};
+use crate::ui::Appearance;
 
 pub struct App {
     pub snapshot: Snapshot,
+    pub config: Config,
+    pub appearance: Appearance,
+    registry: Registry,
     started_at: Instant,
     last_tick: Instant,
 }
 
 impl App {
-    pub fn new_preview() -> Self {
+    pub fn new_preview(config: Config, appearance: Appearance, registry: Registry) -> Self {
         let started_at = Instant::now();
-        let snapshot = data::collect_preview(started_at);
-        let last_tick = Instant::now();
-        Self {
-            snapshot,
+        let mut app = Self {
+            snapshot: Snapshot::default(),
+            config,
+            appearance,
+            registry,
             started_at,
-            last_tick,
-        }
+            last_tick: started_at,
+        };
+        app.tick();
+        app
     }
 
     pub fn tick(&mut self) {
-        self.snapshot = data::collect_preview(self.started_at);
+        let sources = Sources::preview(self.started_at, &self.config);
+        self.snapshot = self
+            .registry
+            .collect(self.appearance.layout.segment_ids(), &sources);
         self.last_tick = Instant::now();
     }
 
@@ -0,0 +1,721 @@
+use anyhow::{anyhow, Context, Result};
+use jiff::tz::TimeZone;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+use toml::{Table, Value};
+
+const APP_NAME: &str = "cc-sakura-line";
+const CONFIG_FILE: &str = "config.toml";
+const PROJECT_FILE: &str = ".cc-sakura-line.toml";
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Fixed statusline width; detected from the terminal when unset.
+    pub width: Option<usize>,
+    pub fill: bool,
+    /// Columns kept free on the right for Claude Code's system notices.
+    pub reserved: usize,
+    pub color: ColorMode,
+    pub glyphs: GlyphStyle,
+    /// Built-in theme name or a key of `themes`; `sakura` when unset.
+    pub theme: Option<String>,
+    pub themes: BTreeMap<String, ThemeConfig>,
+    pub layout: LayoutConfig,
+    pub git: GitConfig,
+    pub github: GithubConfig,
+    pub timeouts: TimeoutsConfig,
+    pub budget: BudgetConfig,
+    pub history: HistoryConfig,
+    pub segments: SegmentsConfig,
+}
+
+/// A user-defined theme: colors are `#rrggbb` or color names, and unset roles
+/// come from `base`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    pub primary: Option<String>,
+    pub primary_fg: Option<String>,
+    pub secondary: Option<String>,
+    pub secondary_fg: Option<String>,
+    pub row_bg: Option<String>,
+    pub row_fg: Option<String>,
+    pub mid_bg: Option<String>,
+    pub mid_fg: Option<String>,
+    pub plus: Option<String>,
+    pub minus: Option<String>,
+    pub calm: Option<String>,
+    pub warn: Option<String>,
+    pub danger: Option<String>,
+}
+
+/// Color depth of the output; `auto` looks at `NO_COLOR`, `COLORTERM` and `TERM`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Truecolor,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "16")]
+    Ansi16,
+    None,
+}
+
+/// Pill end caps: Powerline glyphs need a Nerd Font, `ascii` and `none` do not.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlyphStyle {
+    #[default]
+    Rounded,
+    Angled,
+    Slanted,
+    Ascii,
+    None,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    /// Rows of segment ids, top to bottom; the built-in 3x4 grid when unset.
+    pub rows: Option<Vec<Vec<CellConfig>>>,
+    /// Column widths in percent, used when filling the full width.
+    pub columns: Option<Vec<u16>>,
+}
+
+/// A layout cell: either a bare segment id or `{ id, style, accent }`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum CellConfig {
+    Id(String),
+    Styled {
+        id: String,
+        style: Option<CellStyle>,
+        accent: Option<Accent>,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CellStyle {
+    Pill,
+    Block,
+    /// Colors `+n` / `-m` and the per-kind counts of `git_status`.
+    Diff,
+    /// A bar filled to the percentage the value starts with.
+    Gauge,
+}
+
+/// Which theme colors a pill uses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Accent {
+    #[default]
+    Primary,
+    Secondary,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct GitConfig {
+    pub backend: GitBackendKind,
+    /// Glob the nearest tag must match, like `git describe --match`.
+    pub tag_pattern: Option<String>,
+}
+
+/// How git data is read: `native` uses libgit2 in-process, `cli` runs `git`,
+/// and `auto` falls back to `cli` when `native` fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitBackendKind {
+    #[default]
+    Auto,
+    Native,
+    Cli,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct GithubConfig {
+    pub user: Option<String>,
+    /// Seconds a cached contributions count stays fresh.
+    pub cache_ttl: u64,
+}
+
+impl Default for GithubConfig {
+    fn default() -> Self {
+        Self {
+            user: None,
+            cache_ttl: 300,
+        }
+    }
+}
+
+/// Milliseconds each background source may take before the line is drawn
+/// without it.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TimeoutsConfig {
+    pub git: u64,
+    pub github: u64,
+}
+
+impl Default for TimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            git: 500,
+            github: 1000,
+        }
+    }
+}
+
+/// Spend limits in USD. The spend segments turn amber from `warn` percent
+/// of a limit and red once it is reached.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    pub daily: Option<f64>,
+    pub monthly: Option<f64>,
+    pub warn: f64,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            daily: None,
+            monthly: None,
+            warn: 80.0,
+        }
+    }
+}
+
+/// Whether each render is appended to the history read by `stats`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub enabled: bool,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SegmentsConfig {
+    pub model: ModelConfig,
+    pub version: VersionConfig,
+    pub contributions: ContributionsConfig,
+    pub repository: RepositoryConfig,
+    pub cost: CostConfig,
+    pub context: ContextConfig,
+    pub context_gauge: ContextGaugeConfig,
+    pub clock: ClockConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ModelConfig {
+    pub name: Option<String>,
+}
+
+/// `host = true` prefixes the remote's host: `gitlab.example.com/group/repo`.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct RepositoryConfig {
+    pub host: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct VersionConfig {
+    pub label: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContributionsConfig {
+    /// Fixed count shown instead of querying the GitHub API.
+    pub count: Option<String>,
+}
+
+/// How the session's USD cost is shown; `rate` converts it to another
+/// currency, e.g. `symbol = "¥"`, `rate = 150`, `decimals = 0`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct CostConfig {
+    pub symbol: String,
+    /// Put the symbol after the amount, as in `0.42 €`.
+    pub symbol_after: bool,
+    pub rate: f64,
+    pub decimals: usize,
+}
+
+impl Default for CostConfig {
+    fn default() -> Self {
+        Self {
+            symbol: "$".to_string(),
+            symbol_after: false,
+            rate: 1.0,
+            decimals: 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// Decimals on `K`/`M` token counts.
+    pub precision: usize,
+    pub label: Option<String>,
+    pub used: Option<u64>,
+    pub total: Option<u64>,
+    pub remaining: Option<String>,
+}
+
+/// Usage percentages at which the context gauge changes color, and from
+/// which it flags the coming auto-compact with `!`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ContextGaugeConfig {
+    pub warn: f64,
+    pub danger: f64,
+    pub compact: f64,
+}
+
+impl Default for ContextGaugeConfig {
+    fn default() -> Self {
+        Self {
+            warn: 60.0,
+            danger: 80.0,
+            compact: 90.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    pub twelve_hour: bool,
+    pub seconds: bool,
+    pub date: bool,
+    /// IANA name of a zone to show next to local time, e.g. `America/New_York`.
+    #[serde(with = "jiff::fmt::serde::tz::optional")]
+    pub second_zone: Option<TimeZone>,
+    /// Shown before the second zone's time; its abbreviation when unset.
+    pub second_zone_label: Option<String>,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self {
+            twelve_hour: false,
+            seconds: true,
+            date: false,
+            second_zone: None,
+            second_zone_label: None,
+        }
+    }
+}
+
+/// Settings given on the command line; they win over every other source.
+#[derive(Debug, Default)]
+pub struct CliOverrides {
+    pub config: Option<PathBuf>,
+    pub width: Option<usize>,
+    pub reserved: Option<usize>,
+    pub fill: Option<bool>,
+    pub color: Option<String>,
+    pub glyphs: Option<String>,
+    pub theme: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum EnvKind {
+    Text,
+    Number,
+    Flag,
+}
+
+const ENV_KEYS: &[(&str, &[&str], EnvKind)] = &[
+    ("CC_STATUSLINE_WIDTH", &["width"], EnvKind::Number),
+    ("CC_STATUSLINE_RESERVED", &["reserved"], EnvKind::Number),
+    ("CC_STATUSLINE_FILL", &["fill"], EnvKind::Flag),
+    ("CC_COLOR", &["color"], EnvKind::Text),
+    ("CC_GLYPHS", &["glyphs"], EnvKind::Text),
+    ("CC_THEME", &["theme"], EnvKind::Text),
+    ("CC_GIT_BACKEND", &["git", "backend"], EnvKind::Text),
+    ("CC_GIT_TAG_PATTERN", &["git", "tag_pattern"], EnvKind::Text),
+    ("CC_GITHUB_USER", &["github", "user"], EnvKind::Text),
+    (
+        "CC_GITHUB_CACHE_TTL",
+        &["github", "cache_ttl"],
+        EnvKind::Number,
+    ),
+    ("CC_MODEL", &["segments", "model", "name"], EnvKind::Text),
+    (
+        "CC_VERSION",
+        &["segments", "version", "label"],
+        EnvKind::Text,
+    ),
+    (
+        "CC_CONTRIBUTIONS",
+        &["segments", "contributions", "count"],
+        EnvKind::Text,
+    ),
+    (
+        "CC_CONTEXT_LABEL",
+        &["segments", "context", "label"],
+        EnvKind::Text,
+    ),
+    (
+        "CC_CONTEXT_USED",
+        &["segments", "context", "used"],
+        EnvKind::Number,
+    ),
+    (
+        "CC_CONTEXT_TOTAL",
+        &["segments", "context", "total"],
+        EnvKind::Number,
+    ),
+    (
+        "CC_CONTEXT_REMAINING",
+        &["segments", "context", "remaining"],
+        EnvKind::Text,
+    ),
+];
+
+/// Load the effective configuration.
+///
+/// Precedence, highest first: CLI flags, `CC_*` env vars, the nearest
+/// `.cc-sakura-line.toml` above `workspace` (this process's directory when
+/// unset), the user config file, built-in defaults. A layer that cannot be
+/// read or leaves the settings invalid is reported on stderr and skipped;
+/// each env var and flag is a layer of its own, so one typo does not cost
+/// the rest.
+pub fn load(cli: &CliOverrides, workspace: Option<&Path>) -> Config {
+    let mut layers = Vec::new();
+
+    match &cli.config {
+        Some(path) => layers.push((path.display().to_string(), read_layer(path))),
+        None => {
+            if let Some(path) = user_config_path().filter(|p| p.is_file()) {
+                layers.push((path.display().to_string(), read_layer(&path)));
+            }
+        }
+    }
+
+    if let Some(path) = project_config_path(workspace) {
+        layers.push((path.display().to_string(), read_layer(&path)));
+    }
+
+    layers.extend(env_layers(|key| env::var(key).ok()));
+    layers.extend(cli_layers(cli));
+
+    let (config, skipped) = combine(layers);
+    for error in skipped {
+        eprintln!("{}: {:#}", APP_NAME, error);
+    }
+    config
+}
+
+/// Merge `layers` in order, skipping any that fail or make the merged
+/// settings invalid. Returns what the kept layers add up to, and why each
+/// skipped one was skipped.
+fn combine(layers: Vec<(String, Result<Table>)>) -> (Config, Vec<anyhow::Error>) {
+    let mut merged = Table::new();
+    let mut config = Config::default();
+    let mut skipped = Vec::new();
+
+    for (source, layer) in layers {
+        let checked = layer.and_then(|layer| {
+            let mut next = merged.clone();
+            merge(&mut next, layer);
+            let config = Value::Table(next.clone()).try_into::<Config>()?;
+            Ok((next, config))
+        });
+        match checked {
+            Ok((next, next_config)) => {
+                merged = next;
+                config = next_config;
+            }
+            Err(error) => skipped.push(error.context(format!("ignoring {}", source))),
+        }
+    }
+    (config, skipped)
+}
+
+/// `$XDG_CONFIG_HOME/cc-sakura-line/config.toml`, or `~/.config/...` as fallback.
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = env::var("XDG_CONFIG_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| {
+            env::var("HOME")
+                .ok()
+                .map(|h| PathBuf::from(h).join(".config"))
+        })?;
+    Some(base.join(APP_NAME).join(CONFIG_FILE))
+}
+
+fn project_config_path(workspace: Option<&Path>) -> Option<PathBuf> {
+    let start = match workspace {
+        Some(dir) => dir.to_path_buf(),
+        None => env::current_dir().ok()?,
+    };
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+fn read_layer(path: &Path) -> Result<Table> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("failed to read config {}", path.display()))?;
+    parse_layer(&text).with_context(|| format!("failed to parse config {}", path.display()))
+}
+
+fn parse_layer(text: &str) -> Result<Table> {
+    Ok(text.parse::<Table>()?)
+}
+
+/// One layer per set `CC_*` variable; empty ones count as unset.
+fn env_layers(lookup: impl Fn(&str) -> Option<String>) -> Vec<(String, Result<Table>)> {
+    let mut layers = Vec::new();
+    for (key, path, kind) in ENV_KEYS {
+        let Some(raw) = lookup(key).filter(|v| !v.is_empty()) else {
+            continue;
+        };
+        let value = match kind {
+            EnvKind::Text => Ok(Value::String(raw)),
+            EnvKind::Number => match raw.parse::<i64>() {
+                Ok(n) if n >= 0 => Ok(Value::Integer(n)),
+                _ => Err(anyhow!("`{}` is not a whole number", raw)),
+            },
+            EnvKind::Flag => Ok(Value::Boolean(parse_flag(&raw))),
+        };
+        let layer = value.map(|value| {
+            let mut table = Table::new();
+            set_path(&mut table, path, value);
+            table
+        });
+        layers.push((key.to_string(), layer));
+    }
+    layers
+}
+
+/// One layer per flag given.
+fn cli_layers(cli: &CliOverrides) -> Vec<(String, Result<Table>)> {
+    let flags = [
+        ("width", cli.width.map(|width| Value::Integer(width as i64))),
+        (
+            "reserved",
+            cli.reserved.map(|reserved| Value::Integer(reserved as i64)),
+        ),
+        ("fill", cli.fill.map(Value::Boolean)),
+        ("color", cli.color.clone().map(Value::String)),
+        ("glyphs", cli.glyphs.clone().map(Value::String)),
+        ("theme", cli.theme.clone().map(Value::String)),
+    ];
+    flags
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let mut table = Table::new();
+            set_path(&mut table, &[key], value?);
+            Some((format!("--{}", key), Ok(table)))
+        })
+        .collect()
+}
+
+fn parse_flag(value: &str) -> bool {
+    matches!(value, "1" | "true" | "yes" | "on")
+}
+
+fn set_path(table: &mut Table, path: &[&str], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut current = table;
+    for key in parents {
+        let entry = current
+            .entry(key.to_string())
+            .or_insert_with(|| Value::Table(Table::new()));
+        if !entry.is_table() {
+            *entry = Value::Table(Table::new());
+        }
+        current = match entry {
+            Value::Table(inner) => inner,
+            _ => unreachable!(),
+        };
+    }
+    current.insert(last.to_string(), value);
+}
+
+/// Deep-merge `overlay` into `base`; nested tables merge, everything else replaces.
+fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match value {
+            Value::Table(incoming) => match base.get_mut(&key) {
+                Some(Value::Table(existing)) => merge(existing, incoming),
+                _ => {
+                    base.insert(key, Value::Table(incoming));
+                }
+            },
+            value => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Parse a standalone TOML document into a `Config`, without any other layers.
+#[cfg(test)]
+fn from_str(text: &str) -> Result<Config> {
+    Ok(Value::Table(parse_layer(text)?).try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cli_layers, combine, env_layers, from_str, merge, parse_layer, project_config_path,
+        CliOverrides, ColorMode, Config, GitBackendKind, PROJECT_FILE,
+    };
+    use std::{env, fs, process};
+    use toml::Value;
+
+    #[test]
+    fn defaults_when_empty() {
+        let config = from_str("").unwrap();
+        assert_eq!(config.width, None);
+        assert!(!config.fill);
+        assert_eq!(config.reserved, 0);
+        assert_eq!(config.github.cache_ttl, 300);
+        assert_eq!(config.timeouts.git, 500);
+    }
+
+    #[test]
+    fn parses_nested_sections() {
+        let config = from_str(
+            r#"
+            width = 120
+            reserved = 24
+
+            [github]
+            user = "sakura"
+            cache_ttl = 60
+
+            [segments.context]
+            used = 10
+            total = 200
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.width, Some(120));
+        assert_eq!(config.reserved, 24);
+        assert_eq!(config.github.user.as_deref(), Some("sakura"));
+        assert_eq!(config.github.cache_ttl, 60);
+        assert_eq!(config.segments.context.used, Some(10));
+        assert_eq!(config.segments.context.total, Some(200));
+    }
+
+    #[test]
+    fn clock_zone_must_be_known() {
+        let config = from_str("[segments.clock]\nsecond_zone = \"Asia/Tokyo\"\n").unwrap();
+        assert!(config.segments.clock.second_zone.is_some());
+        assert!(from_str("[segments.clock]\nsecond_zone = \"Mars/Olympus\"\n").is_err());
+    }
+
+    #[test]
+    fn later_layers_win_and_tables_merge() {
+        let mut merged =
+            parse_layer("reserved = 4\n[github]\nuser = \"a\"\ncache_ttl = 10\n").unwrap();
+        merge(
+            &mut merged,
+            parse_layer("reserved = 8\n[github]\nuser = \"b\"\n").unwrap(),
+        );
+        let config: Config = Value::Table(merged).try_into().unwrap();
+        assert_eq!(config.reserved, 8);
+        assert_eq!(config.github.user.as_deref(), Some("b"));
+        assert_eq!(config.github.cache_ttl, 10);
+    }
+
+    #[test]
+    fn env_layer_maps_known_keys() {
+        let (config, skipped) = combine(env_layers(|key| match key {
+            "CC_STATUSLINE_FILL" => Some("yes".to_string()),
+            "CC_STATUSLINE_WIDTH" => Some("not-a-number".to_string()),
+            "CC_MODEL" => Some(String::new()),
+            "CC_CONTEXT_USED" => Some("42".to_string()),
+            _ => None,
+        }));
+        assert!(config.fill);
+        assert_eq!(config.width, None);
+        assert_eq!(config.segments.model.name, None);
+        assert_eq!(config.segments.context.used, Some(42));
+        assert_eq!(skipped.len(), 1);
+    }
+
+    #[test]
+    fn bad_layers_are_skipped() {
+        let mut layers = vec![(
+            "config.toml".to_string(),
+            parse_layer("reserved = 4\ncolor = \"16\"\n"),
+        )];
+        layers.push(("broken.toml".to_string(), parse_layer("reserved = [")));
+        layers.extend(env_layers(|key| match key {
+            "CC_COLOR" => Some("bogus".to_string()),
+            "CC_GIT_BACKEND" => Some("Native".to_string()),
+            "CC_STATUSLINE_RESERVED" => Some("8".to_string()),
+            _ => None,
+        }));
+
+        let (config, skipped) = combine(layers);
+        assert_eq!(config.reserved, 8);
+        assert_eq!(config.color, ColorMode::Ansi16);
+        assert_eq!(config.git.backend, GitBackendKind::default());
+        assert_eq!(skipped.len(), 3);
+        assert!(format!("{:#}", skipped[1]).starts_with("ignoring CC_COLOR"));
+    }
+
+    #[test]
+    fn cli_overrides_env() {
+        let mut layers = env_layers(|key| match key {
+            "CC_STATUSLINE_FILL" => Some("1".to_string()),
+            "CC_STATUSLINE_RESERVED" => Some("10".to_string()),
+            _ => None,
+        });
+        let cli = CliOverrides {
+            fill: Some(false),
+            ..CliOverrides::default()
+        };
+        layers.extend(cli_layers(&cli));
+        let (config, _) = combine(layers);
+        assert!(!config.fill);
+        assert_eq!(config.reserved, 10);
+    }
+
+    #[test]
+    fn project_file_is_found_from_the_workspace() {
+        let root = env::temp_dir().join(format!("cc-sakura-project-{}", process::id()));
+        let workspace = root.join("crates").join("line");
+        fs::create_dir_all(&workspace).unwrap();
+        fs::write(root.join(PROJECT_FILE), "reserved = 4\n").unwrap();
+
+        assert_eq!(
+            project_config_path(Some(&workspace)),
+            Some(root.join(PROJECT_FILE))
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
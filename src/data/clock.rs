@@ -1,8 +1,99 @@
-pub fn now_hms() -> String {
-    let output = std::process::Command::new("date").arg("+%H:%M:%S").output();
+use jiff::{civil::Date, Zoned};
 
-    match output {
-        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).trim().to_string(),
-        _ => "-".to_string(),
+use crate::config::ClockConfig;
+
+/// The current local time, resolved from `TZ` or `/etc/localtime`.
+pub fn now(config: &ClockConfig) -> String {
+    format(&Zoned::now(), config)
+}
+
+/// Today's local date; displays as `YYYY-MM-DD`.
+pub fn today() -> Date {
+    Zoned::now().date()
+}
+
+fn format(now: &Zoned, config: &ClockConfig) -> String {
+    let mut text = String::new();
+    if config.date {
+        text.push_str(&now.strftime("%m-%d ").to_string());
+    }
+    text.push_str(
+        &now.strftime(&time_pattern(config, config.seconds))
+            .to_string(),
+    );
+
+    if let Some(zone) = &config.second_zone {
+        let there = now.with_time_zone(zone.clone());
+        let label = match &config.second_zone_label {
+            Some(label) => label.clone(),
+            None => there.strftime("%Z").to_string(),
+        };
+        // The second zone is for a glance at a teammate's hour; seconds
+        // would only widen the cell.
+        let time = there.strftime(&time_pattern(config, false)).to_string();
+        text.push_str(&format!(" {} {}", label, time));
+    }
+
+    text
+}
+
+fn time_pattern(config: &ClockConfig, seconds: bool) -> String {
+    let mut pattern = if config.twelve_hour {
+        "%-I:%M"
+    } else {
+        "%H:%M"
+    }
+    .to_string();
+    if seconds {
+        pattern.push_str(":%S");
+    }
+    if config.twelve_hour {
+        pattern.push_str(" %p");
+    }
+    pattern
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format;
+    use crate::config::ClockConfig;
+    use jiff::{tz::TimeZone, Zoned};
+
+    fn at(text: &str) -> Zoned {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn default_is_24h_with_seconds() {
+        let now = at("2024-03-10T14:05:09[UTC]");
+        assert_eq!(format(&now, &ClockConfig::default()), "14:05:09");
+    }
+
+    #[test]
+    fn twelve_hour_with_date() {
+        let config = ClockConfig {
+            twelve_hour: true,
+            seconds: false,
+            date: true,
+            ..ClockConfig::default()
+        };
+        let now = at("2024-03-10T14:05:09[UTC]");
+        assert_eq!(format(&now, &config), "03-10 2:05 PM");
+    }
+
+    #[test]
+    fn second_zone_follows_its_offset() {
+        let config = ClockConfig {
+            second_zone: Some(TimeZone::get("Asia/Tokyo").unwrap()),
+            ..ClockConfig::default()
+        };
+        let now = at("2024-03-10T14:05:09[UTC]");
+        assert_eq!(format(&now, &config), "14:05:09 JST 23:05");
+
+        let config = ClockConfig {
+            second_zone_label: Some("TYO".to_string()),
+            ..config
+        };
+        assert_eq!(format(&now, &config), "14:05:09 TYO 23:05");
     }
 }
@@ -1,120 +1,141 @@
-use serde_json::Value;
-use std::env;
-
-use crate::data::claude;
+use crate::config::ContextConfig;
+use crate::data::input::{StatusInput, Usage};
 
 pub struct ContextInfo {
     pub context: String,
     pub remaining: String,
+    /// Current usage split by kind, e.g. `in 1.2K out 830 cw 4.1K cr 38.9K`.
+    pub breakdown: String,
+    /// Share of the window in use, 0–100; `None` when used or total is unknown.
+    pub used_percent: Option<f64>,
 }
 
-pub fn from_input(input: Option<&Value>) -> ContextInfo {
-    let label_override = env_or("CC_CONTEXT_LABEL", "");
-    let remaining_override = env_or("CC_CONTEXT_REMAINING", "");
-
-    let used_env = env_u64("CC_CONTEXT_USED");
-    let total_env = env_u64("CC_CONTEXT_TOTAL");
-    let (used, total) = if used_env.is_some() || total_env.is_some() {
-        (used_env, total_env)
+pub fn from_input(input: &StatusInput, config: &ContextConfig) -> ContextInfo {
+    let (used, total) = if config.used.is_some() || config.total.is_some() {
+        (config.used, config.total)
     } else {
         context_from_json(input)
     };
 
-    let context = if let Some(label) = label_override {
-        if !label.is_empty() {
-            label
-        } else {
-            format_context(used, total)
-        }
-    } else {
-        format_context(used, total)
+    let context = match &config.label {
+        Some(label) if !label.is_empty() => label.clone(),
+        _ => format_context(used, total, config.precision),
     };
 
-    let remaining = if let Some(rem) = remaining_override {
-        if !rem.is_empty() {
-            rem
-        } else {
-            format_remaining(used, total)
-        }
-    } else {
-        format_remaining(used, total)
+    let remaining = match &config.remaining {
+        Some(rem) if !rem.is_empty() => rem.clone(),
+        _ => format_remaining(used, total),
     };
 
-    ContextInfo { context, remaining }
+    ContextInfo {
+        context,
+        remaining,
+        breakdown: format_breakdown(&input.context_window.current_usage, config.precision),
+        used_percent: used_percent(used, total),
+    }
 }
 
-fn context_from_json(input: Option<&Value>) -> (Option<u64>, Option<u64>) {
-    let root = match input {
-        Some(root) => root,
-        None => return (None, None),
-    };
-
-    let total = claude::lookup_u64(root, &["context_window", "context_window_size"]);
-
-    let usage = [
-        claude::lookup_u64(root, &["context_window", "current_usage", "input_tokens"]),
-        claude::lookup_u64(root, &["context_window", "current_usage", "output_tokens"]),
-        claude::lookup_u64(
-            root,
-            &[
-                "context_window",
-                "current_usage",
-                "cache_creation_input_tokens",
-            ],
-        ),
-        claude::lookup_u64(
-            root,
-            &["context_window", "current_usage", "cache_read_input_tokens"],
-        ),
-    ];
+fn context_from_json(input: &StatusInput) -> (Option<u64>, Option<u64>) {
+    let window = &input.context_window;
+    let usage = &window.current_usage;
 
     let mut used_sum: Option<u64> = None;
-    for value in usage.into_iter().flatten() {
+    for value in [
+        usage.input_tokens,
+        usage.output_tokens,
+        usage.cache_creation_input_tokens,
+        usage.cache_read_input_tokens,
+    ]
+    .into_iter()
+    .flatten()
+    {
         used_sum = Some(used_sum.unwrap_or(0) + value);
     }
 
-    (used_sum, total)
+    (used_sum, window.context_window_size)
 }
 
-fn format_context(used: Option<u64>, total: Option<u64>) -> String {
+fn format_context(used: Option<u64>, total: Option<u64>, precision: usize) -> String {
     match (used, total) {
-        (Some(used), Some(total)) if total > 0 => format!("{}/{}", used, total),
-        (Some(used), None) => format!("{}", used),
+        (Some(used), Some(total)) if total > 0 => format!(
+            "{}/{}",
+            format_tokens(used, precision),
+            format_tokens(total, precision)
+        ),
+        (Some(used), None) => format_tokens(used, precision),
         _ => "-".to_string(),
     }
 }
 
+fn format_breakdown(usage: &Usage, precision: usize) -> String {
+    let parts: Vec<String> = [
+        ("in", usage.input_tokens),
+        ("out", usage.output_tokens),
+        ("cw", usage.cache_creation_input_tokens),
+        ("cr", usage.cache_read_input_tokens),
+    ]
+    .into_iter()
+    .filter_map(|(label, tokens)| Some(format!("{} {}", label, format_tokens(tokens?, precision))))
+    .collect();
+
+    if parts.is_empty() {
+        "-".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// `830`, `45K`, `1.2M`: thousands and millions with up to `precision`
+/// decimals, trailing zeros dropped.
+pub fn format_tokens(tokens: u64, precision: usize) -> String {
+    if tokens < 1_000 {
+        return tokens.to_string();
+    }
+
+    let round = |value: f64| {
+        let text = format!("{:.*}", precision, value);
+        if text.contains('.') {
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            text
+        }
+    };
+
+    let thousands = round(tokens as f64 / 1_000.0);
+    // 999_950 rounds up to `1000K`; say `1M` instead.
+    if tokens < 1_000_000 && thousands.parse::<f64>().is_ok_and(|k| k < 1_000.0) {
+        format!("{}K", thousands)
+    } else {
+        format!("{}M", round(tokens as f64 / 1_000_000.0))
+    }
+}
+
 fn format_remaining(used: Option<u64>, total: Option<u64>) -> String {
-    match (used, total) {
-        (Some(used), Some(total)) if total > 0 => {
-            let used_pct = (used as f64 / total as f64) * 100.0;
+    match used_percent(used, total) {
+        Some(used_pct) => {
             let remaining_pct = (100.0 - used_pct).round().max(0.0);
             format!("{}% left", remaining_pct as u64)
         }
-        _ => "-".to_string(),
+        None => "-".to_string(),
     }
 }
 
-/// Read an env var or return a fallback; empty defaults yield `None`.
-pub fn env_or(key: &str, default: &str) -> Option<String> {
-    match env::var(key) {
-        Ok(val) => Some(val),
-        Err(_) if default.is_empty() => None,
-        Err(_) => Some(default.to_string()),
+fn used_percent(used: Option<u64>, total: Option<u64>) -> Option<f64> {
+    match (used, total) {
+        (Some(used), Some(total)) if total > 0 => Some(used as f64 / total as f64 * 100.0),
+        _ => None,
     }
 }
 
-fn env_u64(key: &str) -> Option<u64> {
-    env::var(key).ok().and_then(|v| v.parse::<u64>().ok())
-}
-
 #[cfg(test)]
 mod tests {
-    use super::{format_context, format_remaining};
+    use super::{format_context, format_remaining, format_tokens, from_input};
+    use crate::config::ContextConfig;
+    use crate::data::input;
 
     #[test]
     fn format_context_with_total() {
-        assert_eq!(format_context(Some(120), Some(200)), "120/200");
+        assert_eq!(format_context(Some(120), Some(200), 0), "120/200");
     }
 
     #[test]
@@ -126,4 +147,23 @@ mod tests {
     fn format_remaining_unknown() {
         assert_eq!(format_remaining(None, None), "-");
     }
+
+    #[test]
+    fn sums_current_usage_from_payload() {
+        let input = input::parse(include_str!("../../tests/fixtures/claude-code-2.0.65.json"));
+        let info = from_input(&input, &ContextConfig::default());
+        assert_eq!(info.context, "45K/200K");
+        assert_eq!(info.breakdown, "in 1K out 830 cw 4K cr 39K");
+        assert_eq!(info.remaining, "77% left");
+    }
+
+    #[test]
+    fn token_units() {
+        assert_eq!(format_tokens(830, 1), "830");
+        assert_eq!(format_tokens(45_123, 0), "45K");
+        assert_eq!(format_tokens(45_123, 1), "45.1K");
+        assert_eq!(format_tokens(200_000, 1), "200K");
+        assert_eq!(format_tokens(999_950, 0), "1M");
+        assert_eq!(format_tokens(1_250_000, 2), "1.25M");
+    }
 }
@@ -0,0 +1,135 @@
+use crate::config::{BudgetConfig, CostConfig};
+use crate::data::input::Cost;
+
+/// The session's total cost, converted and formatted per `config`.
+pub fn total(cost: &Cost, config: &CostConfig) -> String {
+    match cost.total_cost_usd {
+        Some(usd) => format_amount(usd * config.rate, config),
+        None => "-".to_string(),
+    }
+}
+
+/// Share of wall time spent waiting on the API, e.g. `api 21%`.
+pub fn api_ratio(cost: &Cost) -> String {
+    match (cost.total_api_duration_ms, cost.total_duration_ms) {
+        (Some(api), Some(wall)) if wall > 0 => {
+            format!("api {}%", (api as f64 / wall as f64 * 100.0).round() as u64)
+        }
+        _ => "-".to_string(),
+    }
+}
+
+/// Lines Claude reports changing this session, as `+n -m`.
+pub fn lines_changed(cost: &Cost) -> String {
+    match (cost.total_lines_added, cost.total_lines_removed) {
+        (None, None) => "-".to_string(),
+        (added, removed) => format!("+{} -{}", added.unwrap_or(0), removed.unwrap_or(0)),
+    }
+}
+
+/// Spend against an optional limit, e.g. `today $4.20/$20.00`.
+pub fn spend(label: &str, usd: f64, limit_usd: Option<f64>, config: &CostConfig) -> String {
+    let spent = format_amount(usd * config.rate, config);
+    match limit_usd {
+        Some(limit) => format!(
+            "{} {}/{}",
+            label,
+            spent,
+            format_amount(limit * config.rate, config)
+        ),
+        None => format!("{} {}", label, spent),
+    }
+}
+
+/// Where spend stands against a limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetState {
+    /// Below the `warn` percentage, or no limit set.
+    Under,
+    Approaching,
+    Reached,
+}
+
+/// How close `usd` is to `limit_usd`, with `budget.warn` percent as the
+/// point where it starts approaching.
+pub fn budget_state(usd: f64, limit_usd: Option<f64>, budget: &BudgetConfig) -> BudgetState {
+    let Some(limit) = limit_usd.filter(|limit| *limit > 0.0) else {
+        return BudgetState::Under;
+    };
+    let percent = usd / limit * 100.0;
+    if percent >= 100.0 {
+        BudgetState::Reached
+    } else if percent >= budget.warn {
+        BudgetState::Approaching
+    } else {
+        BudgetState::Under
+    }
+}
+
+/// `amount`, already converted, with the configured symbol and decimals.
+pub fn format_amount(amount: f64, config: &CostConfig) -> String {
+    let number = format!("{:.*}", config.decimals, amount);
+    if config.symbol_after {
+        format!("{} {}", number, config.symbol)
+    } else {
+        format!("{}{}", config.symbol, number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{api_ratio, budget_state, lines_changed, total, BudgetState};
+    use crate::config::{BudgetConfig, CostConfig};
+    use crate::data::input;
+
+    #[test]
+    fn formats_fixture_cost() {
+        let input = input::parse(include_str!("../../tests/fixtures/claude-code-1.0.88.json"));
+        assert_eq!(total(&input.cost, &CostConfig::default()), "$0.42");
+        assert_eq!(api_ratio(&input.cost), "api 21%");
+        assert_eq!(lines_changed(&input.cost), "+156 -23");
+    }
+
+    #[test]
+    fn converts_currency() {
+        let input = input::parse(include_str!("../../tests/fixtures/claude-code-1.0.88.json"));
+        let yen = CostConfig {
+            symbol: "¥".to_string(),
+            rate: 150.0,
+            decimals: 0,
+            ..CostConfig::default()
+        };
+        assert_eq!(total(&input.cost, &yen), "¥63");
+
+        let euro = CostConfig {
+            symbol: "€".to_string(),
+            symbol_after: true,
+            rate: 0.9,
+            ..CostConfig::default()
+        };
+        assert_eq!(total(&input.cost, &euro), "0.38 €");
+    }
+
+    #[test]
+    fn budget_states_by_share_spent() {
+        let budget = BudgetConfig::default();
+        assert_eq!(budget_state(5.0, Some(10.0), &budget), BudgetState::Under);
+        assert_eq!(
+            budget_state(8.0, Some(10.0), &budget),
+            BudgetState::Approaching
+        );
+        assert_eq!(
+            budget_state(10.0, Some(10.0), &budget),
+            BudgetState::Reached
+        );
+        assert_eq!(budget_state(50.0, None, &budget), BudgetState::Under);
+    }
+
+    #[test]
+    fn missing_cost_is_dash() {
+        let input = input::parse("{}");
+        assert_eq!(total(&input.cost, &CostConfig::default()), "-");
+        assert_eq!(api_ratio(&input.cost), "-");
+        assert_eq!(lines_changed(&input.cost), "-");
+    }
+}
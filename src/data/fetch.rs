@@ -0,0 +1,127 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cell::{Cell, OnceCell, RefCell},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::last_known;
+
+/// A source fetched on its own thread. Reading it waits until the source's
+/// deadline at most, then falls back to the last value saved under its key.
+pub struct Fetch<T> {
+    budget: Duration,
+    /// Where finished values are remembered between runs; `None` keeps
+    /// nothing, for values that are useless once old.
+    key: Option<String>,
+    job: RefCell<Option<Job<T>>>,
+    value: OnceCell<Option<T>>,
+    /// Set when the fetch missed its deadline.
+    late: Cell<bool>,
+}
+
+struct Job<T> {
+    deadline: Instant,
+    rx: Receiver<T>,
+}
+
+impl<T> Fetch<T>
+where
+    T: Serialize + DeserializeOwned + Send + 'static,
+{
+    pub fn new(budget: Duration, key: Option<String>) -> Self {
+        Self {
+            budget,
+            key,
+            job: RefCell::new(None),
+            value: OnceCell::new(),
+            late: Cell::new(false),
+        }
+    }
+
+    /// Start fetching in the background unless already started.
+    pub fn start(&self, fetch: impl FnOnce() -> T + Send + 'static) {
+        if self.value.get().is_some() || self.job.borrow().is_some() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let key = self.key.clone();
+        thread::spawn(move || {
+            let value = fetch();
+            if let Some(key) = &key {
+                last_known::save(key, &value);
+            }
+            let _ = tx.send(value);
+        });
+
+        *self.job.borrow_mut() = Some(Job {
+            deadline: Instant::now() + self.budget,
+            rx,
+        });
+    }
+
+    /// The fetched value, or the last known one if the deadline passed;
+    /// `None` when there is neither.
+    pub fn get(&self, fetch: impl FnOnce() -> T + Send + 'static) -> Option<&T> {
+        self.start(fetch);
+        self.settle(|job| job.deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Like `get`, but without starting the fetch or waiting on it: what has
+    /// arrived already, or else the last known value.
+    pub fn now(&self) -> Option<&T> {
+        self.settle(|_| Duration::ZERO)
+    }
+
+    /// Whether the fetch missed its deadline, leaving the last known value
+    /// or none.
+    pub fn is_late(&self) -> bool {
+        self.late.get()
+    }
+
+    /// Whether the value read is a last known one.
+    pub fn is_stale(&self) -> bool {
+        self.is_late() && self.value.get().is_some_and(Option::is_some)
+    }
+
+    fn settle(&self, wait: impl FnOnce(&Job<T>) -> Duration) -> Option<&T> {
+        self.value
+            .get_or_init(|| {
+                if let Some(job) = self.job.borrow_mut().take() {
+                    match job.rx.recv_timeout(wait(&job)) {
+                        Ok(value) => return Some(value),
+                        Err(_) => self.late.set(true),
+                    }
+                }
+                self.key.as_deref().and_then(last_known::load)
+            })
+            .as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fetch;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn returns_values_within_budget() {
+        let fetch = Fetch::new(Duration::from_secs(5), None);
+        assert_eq!(fetch.get(|| 7u32), Some(&7));
+        assert!(!fetch.is_stale());
+    }
+
+    #[test]
+    fn gives_up_after_the_deadline() {
+        let fetch = Fetch::new(Duration::from_millis(20), None);
+        let value = fetch.get(|| {
+            thread::sleep(Duration::from_secs(2));
+            7u32
+        });
+        assert_eq!(value, None);
+        assert!(fetch.is_late());
+        assert!(!fetch.is_stale());
+    }
+}
@@ -0,0 +1,486 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::{
+    format_ahead_behind, format_changes, format_stash, parse_describe, state, Commit, GitBackend,
+    GitInfo, Query, RemoteInfo, StatusCounts, Tag, NO_UPSTREAM, UPSTREAM_GONE,
+};
+
+/// Shells out to `git`; slower, but understands every repository `git` does.
+pub struct Cli;
+
+impl GitBackend for Cli {
+    fn snapshot(&self, query: &Query) -> Result<GitInfo> {
+        Ok(snapshot(query))
+    }
+}
+
+fn snapshot(query: &Query) -> GitInfo {
+    let dir = query.dir.as_path();
+    let Some((git_dir, common_dir)) = dir.is_dir().then(|| git_dirs(dir)).flatten() else {
+        return GitInfo::outside();
+    };
+
+    let status = git_status(dir);
+    let branch = status
+        .as_ref()
+        .and_then(|out| parse_branch(out))
+        .unwrap_or_else(|| "-".to_string());
+    let branch = state::decorate_branch(branch, &git_dir);
+
+    let changes = status
+        .as_ref()
+        .and_then(|_| line_changes(dir))
+        .map(|(add, del)| format_changes(add, del))
+        .unwrap_or_else(|| "-".to_string());
+
+    let remote = origin_remote(dir);
+    let repository = remote
+        .as_ref()
+        .map(RemoteInfo::path)
+        .or_else(|| get_toplevel_name(dir))
+        .unwrap_or_else(|| "-".to_string());
+
+    let ahead_behind = status
+        .as_ref()
+        .and_then(|out| parse_ahead_behind(out))
+        .unwrap_or_else(|| "-".to_string());
+
+    let upstream = status
+        .as_deref()
+        .map(parse_upstream)
+        .unwrap_or_else(|| "-".to_string());
+
+    let status = status
+        .as_ref()
+        .map(|out| parse_status_counts(out).format())
+        .unwrap_or_else(|| "-".to_string());
+
+    GitInfo {
+        repository,
+        branch,
+        changes,
+        ahead_behind,
+        upstream,
+        status,
+        stash: format_stash(state::stash_count(&common_dir)),
+        remote,
+        last_commit: last_commit(dir),
+        session_commits: query
+            .session_start
+            .and_then(|since| commits_since(dir, since)),
+        tag: nearest_tag(dir, query.tag_pattern.as_deref()),
+    }
+}
+
+/// `git` run in `dir`.
+fn git(dir: &Path) -> Command {
+    let mut command = Command::new("git");
+    command.current_dir(dir);
+    command
+}
+
+/// The repository's git directory and the one shared by all its worktrees;
+/// `None` outside a work tree.
+fn git_dirs(dir: &Path) -> Option<(PathBuf, PathBuf)> {
+    let output = git(dir)
+        .args([
+            "rev-parse",
+            "--is-inside-work-tree",
+            "--absolute-git-dir",
+            "--git-common-dir",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut lines = text.lines();
+    if lines.next()? != "true" {
+        return None;
+    }
+    let git_dir = PathBuf::from(lines.next()?);
+    // Relative to `dir` unless already absolute.
+    let common_dir = dir.join(lines.next()?);
+    Some((git_dir, common_dir))
+}
+
+fn git_status(dir: &Path) -> Option<String> {
+    let output = git(dir)
+        .args(["status", "--porcelain=v2", "--branch"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout).to_string();
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+fn last_commit(dir: &Path) -> Option<Commit> {
+    let output = git(dir)
+        .args(["log", "-1", "--format=%h%x00%ct%x00%s"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_last_commit(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_last_commit(log_output: &str) -> Option<Commit> {
+    let mut fields = log_output.trim_end_matches('\n').splitn(3, '\0');
+    Some(Commit {
+        short_id: fields.next().filter(|id| !id.is_empty())?.to_string(),
+        time: fields.next()?.parse().ok()?,
+        subject: fields.next().unwrap_or_default().to_string(),
+    })
+}
+
+fn nearest_tag(dir: &Path, pattern: Option<&str>) -> Option<Tag> {
+    let mut command = git(dir);
+    command.args(["describe", "--tags", "--long"]);
+    if let Some(pattern) = pattern {
+        command.args(["--match", pattern]);
+    }
+    let output = command.arg("HEAD").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_describe(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn commits_since(dir: &Path, since: i64) -> Option<usize> {
+    let output = git(dir)
+        .args(["rev-list", "--count", &format!("--since={}", since), "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        // No commits yet.
+        return Some(0);
+    }
+    String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+}
+
+fn line_changes(dir: &Path) -> Option<(u64, u64)> {
+    let (add1, del1) = git_numstat(dir, &["diff", "--numstat"]).unwrap_or((0, 0));
+    let (add2, del2) = git_numstat(dir, &["diff", "--numstat", "--cached"]).unwrap_or((0, 0));
+    Some((add1 + add2, del1 + del2))
+}
+
+fn git_numstat(dir: &Path, args: &[&str]) -> Option<(u64, u64)> {
+    let output = git(dir).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    Some(parse_numstat_sum(&text))
+}
+
+fn parse_numstat_sum(text: &str) -> (u64, u64) {
+    let mut add = 0u64;
+    let mut del = 0u64;
+    for line in text.lines() {
+        let mut parts = line.split_whitespace();
+        let a = parts.next();
+        let d = parts.next();
+        if let (Some(a), Some(d)) = (a, d) {
+            if let Ok(v) = a.parse::<u64>() {
+                add += v;
+            }
+            if let Ok(v) = d.parse::<u64>() {
+                del += v;
+            }
+        }
+    }
+    (add, del)
+}
+
+/// Tally the file entries of `git status --porcelain=v2`: `1` ordinary,
+/// `2` renamed or copied, `u` unmerged and `?` untracked.
+fn parse_status_counts(status_output: &str) -> StatusCounts {
+    let mut counts = StatusCounts::default();
+    for line in status_output.lines() {
+        let mut fields = line.split(' ');
+        match fields.next() {
+            Some("?") => counts.untracked += 1,
+            Some("u") => counts.conflicted += 1,
+            Some("1" | "2") => {
+                let mut code = fields.next().unwrap_or("").chars();
+                let (Some(x), Some(y)) = (code.next(), code.next()) else {
+                    continue;
+                };
+                if matches!(x, 'M' | 'A' | 'C' | 'T') {
+                    counts.staged += 1;
+                }
+                if x == 'R' {
+                    counts.renamed += 1;
+                }
+                if x == 'D' || y == 'D' {
+                    counts.deleted += 1;
+                }
+                if matches!(y, 'M' | 'T') {
+                    counts.modified += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    counts
+}
+
+/// The `# branch.*` headers of `git status --porcelain=v2 --branch`.
+#[derive(Debug, Default)]
+struct BranchHeaders<'a> {
+    /// `(initial)` on a branch with no commits yet.
+    oid: Option<&'a str>,
+    /// `(detached)` when HEAD is not on a branch.
+    head: Option<&'a str>,
+    upstream: Option<&'a str>,
+    /// Missing when the upstream is set but its ref is gone.
+    ab: Option<(usize, usize)>,
+}
+
+fn parse_headers(status_output: &str) -> BranchHeaders<'_> {
+    let mut headers = BranchHeaders::default();
+    for line in status_output.lines() {
+        let Some((key, value)) = line
+            .strip_prefix("# branch.")
+            .and_then(|rest| rest.split_once(' '))
+        else {
+            continue;
+        };
+        match key {
+            "oid" => headers.oid = Some(value),
+            "head" => headers.head = Some(value),
+            "upstream" => headers.upstream = Some(value),
+            "ab" => {
+                let mut counts = value
+                    .split(' ')
+                    .map(|count| count.trim_start_matches(['+', '-']).parse().ok());
+                if let (Some(Some(ahead)), Some(Some(behind))) = (counts.next(), counts.next()) {
+                    headers.ab = Some((ahead, behind));
+                }
+            }
+            _ => {}
+        }
+    }
+    headers
+}
+
+fn parse_branch(status_output: &str) -> Option<String> {
+    match parse_headers(status_output).head? {
+        "(detached)" => Some("detached".to_string()),
+        "" => None,
+        name => Some(name.to_string()),
+    }
+}
+
+fn parse_upstream(status_output: &str) -> String {
+    parse_headers(status_output)
+        .upstream
+        .unwrap_or("-")
+        .to_string()
+}
+
+/// `origin`, with `insteadOf` rewrites applied by `get-url`.
+fn origin_remote(dir: &Path) -> Option<RemoteInfo> {
+    let output = git(dir)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    RemoteInfo::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn get_toplevel_name(dir: &Path) -> Option<String> {
+    let output = git(dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    path.split('/').next_back().map(|s| s.to_string())
+}
+
+fn parse_ahead_behind(status_output: &str) -> Option<String> {
+    let headers = parse_headers(status_output);
+    if headers.head? == "(detached)" || headers.oid == Some("(initial)") {
+        return Some("-".to_string());
+    }
+
+    Some(match (headers.upstream, headers.ab) {
+        (None, _) => NO_UPSTREAM.to_string(),
+        (Some(_), None) => UPSTREAM_GONE.to_string(),
+        (Some(_), Some((ahead, behind))) => format_ahead_behind(ahead, behind),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        git_dirs, parse_ahead_behind, parse_branch, parse_last_commit, parse_numstat_sum,
+        parse_status_counts, parse_upstream, snapshot,
+    };
+    use crate::data::git::{
+        fixture::{git, repo, Scratch},
+        Query, StatusCounts, NO_REPO,
+    };
+    use std::fs;
+
+    #[test]
+    fn worktrees_share_the_common_dir() {
+        let scratch = Scratch::new("worktree");
+        let main = scratch.0.join("main");
+        fs::create_dir(&main).unwrap();
+        repo(&main);
+        fs::write(main.join("a.txt"), "stashed\n").unwrap();
+        git(&main, &["stash", "-q"]);
+        git(
+            &main,
+            &["worktree", "add", "-q", "-b", "feature", "../feature"],
+        );
+        let feature = scratch.0.join("feature");
+
+        let (git_dir, common_dir) = git_dirs(&feature).unwrap();
+        assert_eq!(git_dir, main.join(".git/worktrees/feature"));
+        assert_eq!(common_dir.canonicalize().unwrap(), main.join(".git"));
+        assert_eq!(git_dirs(&main.join(".git")), None);
+
+        let query = |dir| Query {
+            dir,
+            session_start: None,
+            tag_pattern: None,
+        };
+        let info = snapshot(&query(feature));
+        assert_eq!(info.branch, "feature");
+        assert_eq!(info.stash, "stash 1");
+        assert_eq!(snapshot(&query(scratch.0.clone())).repository, NO_REPO);
+    }
+
+    #[test]
+    fn parse_branch_from_status() {
+        let output = "# branch.oid 1a2b3c\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +0 -0\n1 .M N... 100644 100644 100644 1a2b 1a2b src/main.rs\n";
+        assert_eq!(parse_branch(output).as_deref(), Some("main"));
+        assert_eq!(parse_upstream(output), "origin/main");
+    }
+
+    #[test]
+    fn parse_branch_detached() {
+        let output = "# branch.oid 1a2b3c\n# branch.head (detached)\n";
+        assert_eq!(parse_branch(output).as_deref(), Some("detached"));
+        assert_eq!(parse_ahead_behind(output).as_deref(), Some("-"));
+    }
+
+    #[test]
+    fn parse_last_commit_fields() {
+        let commit = parse_last_commit("1a2b3c4\x001710072000\x00Fix: a | b\n").unwrap();
+        assert_eq!(commit.short_id, "1a2b3c4");
+        assert_eq!(commit.time, 1_710_072_000);
+        assert_eq!(commit.subject, "Fix: a | b");
+        assert!(parse_last_commit("").is_none());
+    }
+
+    #[test]
+    fn parse_numstat_totals() {
+        let output = "10\t2\tsrc/main.rs\n3\t0\tREADME.md\n";
+        assert_eq!(parse_numstat_sum(output), (13, 2));
+    }
+
+    #[test]
+    fn parse_numstat_ignores_binary() {
+        let output = "-\t-\timage.png\n1\t5\tfile.txt\n";
+        assert_eq!(parse_numstat_sum(output), (1, 5));
+    }
+
+    #[test]
+    fn parse_ahead_behind_both() {
+        let output = "# branch.oid 1a2b3c\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +2 -1\n";
+        assert_eq!(
+            parse_ahead_behind(output).as_deref(),
+            Some("diverged ↑2 ↓1")
+        );
+    }
+
+    #[test]
+    fn parse_ahead_behind_synced() {
+        let output = "# branch.oid 1a2b3c\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +0 -0\n";
+        assert_eq!(parse_ahead_behind(output).as_deref(), Some("synced"));
+    }
+
+    #[test]
+    fn parse_ahead_behind_one_way() {
+        let ahead = "# branch.oid 1a2b3c\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +3 -0\n";
+        assert_eq!(parse_ahead_behind(ahead).as_deref(), Some("↑3"));
+        let behind = "# branch.oid 1a2b3c\n# branch.head main\n# branch.upstream origin/main\n# branch.ab +0 -4\n";
+        assert_eq!(parse_ahead_behind(behind).as_deref(), Some("↓4"));
+    }
+
+    #[test]
+    fn parse_ahead_behind_gone() {
+        let output =
+            "# branch.oid 1a2b3c\n# branch.head feature\n# branch.upstream origin/feature\n";
+        assert_eq!(parse_ahead_behind(output).as_deref(), Some("gone"));
+        assert_eq!(parse_upstream(output), "origin/feature");
+    }
+
+    #[test]
+    fn parse_ahead_behind_unborn() {
+        let output = "# branch.oid (initial)\n# branch.head main\n";
+        assert_eq!(parse_ahead_behind(output).as_deref(), Some("-"));
+    }
+
+    #[test]
+    fn parse_ahead_behind_no_remote() {
+        let output = "# branch.oid 1a2b3c\n# branch.head feature-branch\n";
+        assert_eq!(parse_ahead_behind(output).as_deref(), Some("no upstream"));
+        assert_eq!(parse_upstream(output), "-");
+    }
+
+    #[test]
+    fn parse_status_counts_by_kind() {
+        let output = concat!(
+            "# branch.oid 1a2b3c\n# branch.head main\n",
+            "1 M. N... 100644 100644 100644 1a2b 3c4d staged.rs\n",
+            "1 MM N... 100644 100644 100644 1a2b 3c4d both.rs\n",
+            "1 .M N... 100644 100644 100644 1a2b 1a2b edited.rs\n",
+            "1 .D N... 100644 100644 000000 1a2b 1a2b gone.rs\n",
+            "2 R. N... 100644 100644 100644 1a2b 1a2b R100 new.rs\told.rs\n",
+            "u UU N... 100644 100644 100644 100644 1a2b 3c4d 5e6f clash.rs\n",
+            "? notes/\n",
+        );
+        assert_eq!(
+            parse_status_counts(output),
+            StatusCounts {
+                staged: 2,
+                modified: 2,
+                untracked: 1,
+                deleted: 1,
+                renamed: 1,
+                conflicted: 1,
+            }
+        );
+        assert_eq!(parse_status_counts(output).format(), "●2 ✚2 …1 ✖1 »1 =1");
+        assert_eq!(
+            parse_status_counts("# branch.oid 1a2b3c\n# branch.head main\n").format(),
+            "clean"
+        );
+    }
+}
@@ -0,0 +1,291 @@
+mod cli;
+mod native;
+mod remote;
+mod state;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::config::GitBackendKind;
+
+pub use remote::RemoteInfo;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GitInfo {
+    pub repository: String,
+    pub branch: String,
+    pub changes: String,
+    pub ahead_behind: String,
+    /// The tracked branch, e.g. `origin/main`.
+    #[serde(default)]
+    pub upstream: String,
+    /// Files by kind of change, e.g. `●3 ✚2 …1`.
+    #[serde(default)]
+    pub status: String,
+    /// Stash entries, e.g. `stash 2`.
+    #[serde(default)]
+    pub stash: String,
+    /// Parsed from `origin`'s URL.
+    #[serde(default)]
+    pub remote: Option<RemoteInfo>,
+    #[serde(default)]
+    pub last_commit: Option<Commit>,
+    /// Commits on HEAD made since the Claude session started.
+    #[serde(default)]
+    pub session_commits: Option<usize>,
+    /// The nearest tag reachable from HEAD.
+    #[serde(default)]
+    pub tag: Option<Tag>,
+}
+
+/// A tag and how far HEAD has moved past it; `since` is 0 on the tag.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub since: usize,
+}
+
+/// The commit HEAD points at.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Commit {
+    pub short_id: String,
+    /// Committer time, in seconds since the Unix epoch.
+    pub time: i64,
+    pub subject: String,
+}
+
+/// What to read.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Query {
+    /// Any directory inside the repository.
+    pub dir: PathBuf,
+    /// When the Claude session started, in seconds since the Unix epoch.
+    pub session_start: Option<i64>,
+    /// Glob for the tags `GitInfo::tag` considers.
+    pub tag_pattern: Option<String>,
+}
+
+/// Files in the working tree and index, by kind of change. A file both
+/// staged and modified again counts as each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatusCounts {
+    pub staged: usize,
+    pub modified: usize,
+    pub untracked: usize,
+    pub deleted: usize,
+    pub renamed: usize,
+    pub conflicted: usize,
+}
+
+impl StatusCounts {
+    /// Nonzero counts behind their symbols, or `clean`.
+    pub fn format(&self) -> String {
+        let parts: Vec<String> = [
+            ('●', self.staged),
+            ('✚', self.modified),
+            ('…', self.untracked),
+            ('✖', self.deleted),
+            ('»', self.renamed),
+            ('=', self.conflicted),
+        ]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(symbol, count)| format!("{}{}", symbol, count))
+        .collect();
+
+        if parts.is_empty() {
+            "clean".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+/// Shown as the repository when the directory is not inside one.
+pub const NO_REPO: &str = "no repo";
+
+/// Ahead/behind for a branch that tracks nothing.
+const NO_UPSTREAM: &str = "no upstream";
+/// Ahead/behind for a branch whose upstream was deleted, e.g. after its
+/// pull request was merged.
+const UPSTREAM_GONE: &str = "gone";
+
+/// Reads the repository containing the query's directory.
+pub trait GitBackend {
+    /// Outside a repository this still succeeds, as `GitInfo::outside`; an
+    /// error means the backend could not read a repository that is there.
+    fn snapshot(&self, query: &Query) -> Result<GitInfo>;
+}
+
+/// Snapshot with the configured backend; `auto` tries the in-process reader
+/// first and falls back to the `git` CLI if it fails.
+pub fn snapshot(kind: GitBackendKind, query: &Query) -> GitInfo {
+    let cli = || {
+        cli::Cli
+            .snapshot(query)
+            .unwrap_or_else(|_| GitInfo::empty())
+    };
+    match kind {
+        GitBackendKind::Cli => cli(),
+        GitBackendKind::Native => native::Native
+            .snapshot(query)
+            .unwrap_or_else(|_| GitInfo::empty()),
+        GitBackendKind::Auto => native::Native.snapshot(query).unwrap_or_else(|_| cli()),
+    }
+}
+
+impl GitInfo {
+    fn empty() -> Self {
+        Self {
+            repository: "-".to_string(),
+            branch: "-".to_string(),
+            changes: "-".to_string(),
+            ahead_behind: "-".to_string(),
+            upstream: "-".to_string(),
+            status: "-".to_string(),
+            stash: "-".to_string(),
+            remote: None,
+            last_commit: None,
+            session_commits: None,
+            tag: None,
+        }
+    }
+
+    /// For a directory outside any repository, said so in the repository
+    /// cell rather than left as `-` like a failed read.
+    fn outside() -> Self {
+        Self {
+            repository: NO_REPO.to_string(),
+            ..Self::empty()
+        }
+    }
+}
+
+fn format_changes(add: u64, del: u64) -> String {
+    format!("+{} -{}", add, del)
+}
+
+/// Parse `git describe --long` output, `<tag>-<count>-g<hash>`. The tag
+/// itself may contain dashes, so it is split from the right.
+fn parse_describe(text: &str) -> Option<Tag> {
+    let mut parts = text.trim().rsplitn(3, '-');
+    let hash = parts.next()?;
+    let since = parts.next()?.parse().ok()?;
+    let name = parts.next().filter(|name| !name.is_empty())?;
+    if !hash.starts_with('g') {
+        return None;
+    }
+    Some(Tag {
+        name: name.to_string(),
+        since,
+    })
+}
+
+fn format_stash(count: usize) -> String {
+    if count == 0 {
+        "-".to_string()
+    } else {
+        format!("stash {}", count)
+    }
+}
+
+/// Counts against the upstream: `synced`, `↑2`, `↓1`, or `diverged ↑2 ↓1`
+/// when both sides have commits the other lacks.
+fn format_ahead_behind(ahead: usize, behind: usize) -> String {
+    match (ahead, behind) {
+        (0, 0) => "synced".to_string(),
+        (ahead, 0) => format!("↑{}", ahead),
+        (0, behind) => format!("↓{}", behind),
+        (ahead, behind) => format!("diverged ↑{} ↓{}", ahead, behind),
+    }
+}
+
+/// Scratch repositories for tests, built with the `git` CLI.
+#[cfg(test)]
+pub mod fixture {
+    use std::{
+        env, fs,
+        path::{Path, PathBuf},
+        process::{self, Command},
+    };
+
+    /// A directory under the temp dir, removed on drop.
+    pub struct Scratch(pub PathBuf);
+
+    impl Scratch {
+        pub fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("cc-sakura-{}-{}", name, process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            // Canonical, so paths compare equal to what `git` reports.
+            Self(path.canonicalize().unwrap())
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Run `git` in `dir`, ignoring the user's own config, and return its
+    /// stdout; panics if it fails.
+    pub fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .args([
+                "-c",
+                "user.name=Sakura",
+                "-c",
+                "user.email=sakura@example.com",
+            ])
+            .args([
+                "-c",
+                "init.defaultBranch=main",
+                "-c",
+                "commit.gpgsign=false",
+            ])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?}: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    /// A repository with one commit of `a.txt`.
+    pub fn repo(dir: &Path) {
+        git(dir, &["init", "-q"]);
+        fs::write(dir.join("a.txt"), "one\ntwo\n").unwrap();
+        git(dir, &["add", "a.txt"]);
+        git(dir, &["commit", "-qm", "first"]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_describe, Tag};
+
+    #[test]
+    fn parse_describe_long_format() {
+        let tag = |name: &str, since| {
+            Some(Tag {
+                name: name.to_string(),
+                since,
+            })
+        };
+        assert_eq!(parse_describe("v1.2.3-0-g1a2b3c4\n"), tag("v1.2.3", 0));
+        assert_eq!(
+            parse_describe("crate-v1.2.3-14-g1a2b3c4"),
+            tag("crate-v1.2.3", 14)
+        );
+        assert_eq!(parse_describe("1a2b3c4"), None);
+    }
+}
@@ -0,0 +1,295 @@
+use anyhow::Result;
+use git2::{
+    DescribeFormatOptions, DescribeOptions, DiffOptions, ErrorCode, Repository, Sort, Status,
+    StatusOptions,
+};
+
+use super::{
+    format_ahead_behind, format_changes, format_stash, parse_describe, state, Commit, GitBackend,
+    GitInfo, Query, RemoteInfo, StatusCounts, Tag, NO_UPSTREAM, UPSTREAM_GONE,
+};
+
+/// Reads the repository in-process through libgit2, without spawning `git`.
+pub struct Native;
+
+impl GitBackend for Native {
+    fn snapshot(&self, query: &Query) -> Result<GitInfo> {
+        let repo = match Repository::discover(&query.dir) {
+            Ok(repo) => repo,
+            Err(err) if err.code() == ErrorCode::NotFound => return Ok(GitInfo::outside()),
+            Err(err) => return Err(err.into()),
+        };
+        // A bare repository, or a directory inside `.git`, is no work tree;
+        // the CLI backend says so through `--is-inside-work-tree`.
+        let dir = query
+            .dir
+            .canonicalize()
+            .unwrap_or_else(|_| query.dir.clone());
+        if repo.workdir().is_none() || dir.starts_with(repo.path()) {
+            return Ok(GitInfo::outside());
+        }
+
+        let (add, del) = line_changes(&repo)?;
+        let remote = origin_remote(&repo);
+        Ok(GitInfo {
+            repository: repository_name(&repo, remote.as_ref()).unwrap_or_else(|| "-".to_string()),
+            branch: state::decorate_branch(branch(&repo)?, repo.path()),
+            changes: format_changes(add, del),
+            ahead_behind: ahead_behind(&repo)?,
+            upstream: upstream_name(&repo)
+                .and_then(|name| short_upstream(&name))
+                .unwrap_or_else(|| "-".to_string()),
+            status: status_counts(&repo)?.format(),
+            stash: format_stash(repo.reflog("refs/stash")?.len()),
+            remote,
+            last_commit: last_commit(&repo)?,
+            session_commits: query
+                .session_start
+                .map(|since| commits_since(&repo, since))
+                .transpose()?,
+            tag: nearest_tag(&repo, query.tag_pattern.as_deref()),
+        })
+    }
+}
+
+fn branch(repo: &Repository) -> Result<String> {
+    if repo.head_detached()? {
+        return Ok("detached".to_string());
+    }
+
+    // Unlike `repo.head()`, this also names a branch with no commits yet.
+    let head = repo.find_reference("HEAD")?;
+    let name = head
+        .symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .unwrap_or("-");
+    Ok(name.to_string())
+}
+
+/// Staged plus unstaged lines, like `git diff --numstat` and
+/// `git diff --numstat --cached` together.
+fn line_changes(repo: &Repository) -> Result<(u64, u64)> {
+    let head_tree = match repo.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(err) if err.code() == ErrorCode::UnbornBranch => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let staged =
+        repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut DiffOptions::new()))?;
+    let unstaged = repo.diff_index_to_workdir(None, Some(&mut DiffOptions::new()))?;
+
+    let mut add = 0;
+    let mut del = 0;
+    for mut diff in [staged, unstaged] {
+        // `git diff` pairs up renames, so a moved file counts only its edits.
+        diff.find_similar(None)?;
+        let stats = diff.stats()?;
+        add += stats.insertions() as u64;
+        del += stats.deletions() as u64;
+    }
+    Ok((add, del))
+}
+
+/// Counted like `git status --porcelain`: untracked directories are one
+/// entry, and staged renames are paired up.
+fn status_counts(repo: &Repository) -> Result<StatusCounts> {
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(false)
+        .renames_head_to_index(true);
+
+    let mut counts = StatusCounts::default();
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let status = entry.status();
+        if status.is_conflicted() {
+            counts.conflicted += 1;
+            continue;
+        }
+        if status.is_wt_new() {
+            counts.untracked += 1;
+            continue;
+        }
+        if status.intersects(Status::INDEX_NEW | Status::INDEX_MODIFIED | Status::INDEX_TYPECHANGE)
+        {
+            counts.staged += 1;
+        }
+        if status.is_index_renamed() {
+            counts.renamed += 1;
+        }
+        if status.intersects(Status::INDEX_DELETED | Status::WT_DELETED) {
+            counts.deleted += 1;
+        }
+        if status.intersects(Status::WT_MODIFIED | Status::WT_TYPECHANGE) {
+            counts.modified += 1;
+        }
+    }
+    Ok(counts)
+}
+
+fn last_commit(repo: &Repository) -> Result<Option<Commit>> {
+    let head = match repo.head() {
+        Ok(head) => head.peel_to_commit()?,
+        Err(err) if err.code() == ErrorCode::UnbornBranch => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let short_id = head.as_object().short_id()?;
+    Ok(Some(Commit {
+        short_id: short_id.as_str().unwrap_or_default().to_string(),
+        time: head.time().seconds(),
+        subject: head.summary().unwrap_or_default().to_string(),
+    }))
+}
+
+/// Like `git rev-list --count --since=<since> HEAD`.
+fn commits_since(repo: &Repository, since: i64) -> Result<usize> {
+    let mut walk = repo.revwalk()?;
+    walk.set_sorting(Sort::TIME)?;
+    match walk.push_head() {
+        Ok(()) => {}
+        Err(err) if err.code() == ErrorCode::UnbornBranch => return Ok(0),
+        Err(err) => return Err(err.into()),
+    }
+
+    let mut count = 0;
+    for id in walk {
+        if repo.find_commit(id?)?.time().seconds() < since {
+            break;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Like `git describe --tags --long --match <pattern>`; `None` when no tag
+/// is reachable.
+fn nearest_tag(repo: &Repository, pattern: Option<&str>) -> Option<Tag> {
+    let mut options = DescribeOptions::new();
+    options.describe_tags();
+    if let Some(pattern) = pattern {
+        options.pattern(pattern);
+    }
+    let describe = repo.describe(&options).ok()?;
+    let text = describe
+        .format(Some(
+            DescribeFormatOptions::new().always_use_long_format(true),
+        ))
+        .ok()?;
+    parse_describe(&text)
+}
+
+fn ahead_behind(repo: &Repository) -> Result<String> {
+    let head = match repo.head() {
+        Ok(head) if head.is_branch() => head,
+        Ok(_) => return Ok("-".to_string()),
+        Err(err) if err.code() == ErrorCode::UnbornBranch => return Ok("-".to_string()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let Some(upstream) = upstream_name(repo) else {
+        return Ok(NO_UPSTREAM.to_string());
+    };
+    let upstream = match repo.find_reference(&upstream) {
+        Ok(upstream) => upstream,
+        Err(err) if err.code() == ErrorCode::NotFound => return Ok(UPSTREAM_GONE.to_string()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let (Some(local), Some(remote)) = (head.target(), upstream.target()) else {
+        return Ok("-".to_string());
+    };
+    let (ahead, behind) = repo.graph_ahead_behind(local, remote)?;
+    Ok(format_ahead_behind(ahead, behind))
+}
+
+/// The ref the current branch is configured to track, such as
+/// `refs/remotes/origin/main`, whether or not that ref still exists.
+fn upstream_name(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    let branch = head.symbolic_target()?;
+    let name = repo.branch_upstream_name(branch).ok()?;
+    name.as_str().map(str::to_string)
+}
+
+/// `origin/main`, as `git status` names an upstream.
+fn short_upstream(name: &str) -> Option<String> {
+    let short = name
+        .strip_prefix("refs/remotes/")
+        .or_else(|| name.strip_prefix("refs/heads/"))?;
+    Some(short.to_string())
+}
+
+/// `origin`; libgit2 applies `insteadOf` rewrites when loading it.
+fn origin_remote(repo: &Repository) -> Option<RemoteInfo> {
+    let remote = repo.find_remote("origin").ok()?;
+    RemoteInfo::parse(remote.url()?)
+}
+
+fn repository_name(repo: &Repository, remote: Option<&RemoteInfo>) -> Option<String> {
+    remote.map(RemoteInfo::path).or_else(|| {
+        let dir = repo.workdir()?.file_name()?;
+        Some(dir.to_string_lossy().into_owned())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GitBackendKind;
+    use crate::data::git::{
+        fixture::{git, repo, Scratch},
+        snapshot, Query, NO_REPO,
+    };
+    use std::fs;
+
+    #[test]
+    fn matches_the_cli_backend() {
+        let scratch = Scratch::new("native");
+        let dir = &scratch.0;
+        repo(dir);
+        git(dir, &["tag", "v1.0.0"]);
+        git(
+            dir,
+            &["remote", "add", "origin", "git@github.com:sakura/line.git"],
+        );
+        git(dir, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        git(dir, &["branch", "-q", "--set-upstream-to=origin/main"]);
+
+        fs::write(dir.join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        git(dir, &["commit", "-qam", "second"]);
+        fs::write(dir.join("a.txt"), "stashed\n").unwrap();
+        git(dir, &["stash", "-q"]);
+        fs::write(dir.join("b.txt"), "staged\n").unwrap();
+        git(dir, &["add", "b.txt"]);
+        fs::write(dir.join("a.txt"), "one\n").unwrap();
+        fs::write(dir.join("c.txt"), "untracked\n").unwrap();
+
+        let query = Query {
+            dir: dir.clone(),
+            // `git rev-list --since=0` counts nothing, so start just after the epoch.
+            session_start: Some(1),
+            tag_pattern: None,
+        };
+        let native = snapshot(GitBackendKind::Native, &query);
+        assert_eq!(native, snapshot(GitBackendKind::Cli, &query));
+
+        assert_eq!(native.repository, "sakura/line");
+        assert_eq!(native.branch, "main");
+        assert_eq!(native.changes, "+1 -2");
+        assert_eq!(native.status, "●1 ✚1 …1");
+        assert_eq!(native.stash, "stash 1");
+        assert_eq!(native.ahead_behind, "↑1");
+        assert_eq!(native.upstream, "origin/main");
+        assert_eq!(native.session_commits, Some(2));
+        let tag = native.tag.unwrap();
+        assert_eq!((tag.name.as_str(), tag.since), ("v1.0.0", 1));
+
+        let inside_git_dir = Query {
+            dir: dir.join(".git"),
+            ..query
+        };
+        let native = snapshot(GitBackendKind::Native, &inside_git_dir);
+        assert_eq!(native, snapshot(GitBackendKind::Cli, &inside_git_dir));
+        assert_eq!(native.repository, NO_REPO);
+    }
+}
@@ -0,0 +1,91 @@
+use std::{fs, path::Path};
+
+/// The branch, decorated with any operation in progress: `main|REBASE 3/7`.
+/// Mid-rebase or bisect HEAD is detached, so the branch name comes from the
+/// operation's own files instead.
+pub fn decorate_branch(branch: String, git_dir: &Path) -> String {
+    let read = |name: &str| {
+        fs::read_to_string(git_dir.join(name))
+            .ok()
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    let step = |dir: &str, current: &str, total: &str| {
+        let current = read(&format!("{}/{}", dir, current))?;
+        let total = read(&format!("{}/{}", dir, total))?;
+        Some(format!(" {}/{}", current, total))
+    };
+
+    let (label, head_name, step) = if git_dir.join("rebase-merge").is_dir() {
+        (
+            "REBASE",
+            read("rebase-merge/head-name"),
+            step("rebase-merge", "msgnum", "end"),
+        )
+    } else if git_dir.join("rebase-apply").is_dir() {
+        let label = if git_dir.join("rebase-apply/applying").exists() {
+            "AM"
+        } else {
+            "REBASE"
+        };
+        (
+            label,
+            read("rebase-apply/head-name"),
+            step("rebase-apply", "next", "last"),
+        )
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        ("MERGE", None, None)
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        ("CHERRY-PICK", None, None)
+    } else if git_dir.join("REVERT_HEAD").exists() {
+        ("REVERT", None, None)
+    } else if git_dir.join("BISECT_LOG").exists() {
+        ("BISECT", read("BISECT_START"), None)
+    } else {
+        return branch;
+    };
+
+    let name = head_name
+        .map(|name| name.trim_start_matches("refs/heads/").to_string())
+        .unwrap_or(branch);
+    format!("{}|{}{}", name, label, step.unwrap_or_default())
+}
+
+/// Stash entries, one per line of the `refs/stash` reflog.
+pub fn stash_count(common_dir: &Path) -> usize {
+    fs::read_to_string(common_dir.join("logs/refs/stash"))
+        .map(|log| log.lines().filter(|line| !line.is_empty()).count())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decorate_branch, stash_count};
+    use crate::data::git::fixture::Scratch;
+    use std::fs;
+
+    #[test]
+    fn names_the_operation_in_progress() {
+        let scratch = Scratch::new("git-state");
+        let dir = &scratch.0;
+        fs::create_dir_all(dir.join("rebase-merge")).unwrap();
+        let decorate = || decorate_branch("detached".to_string(), dir);
+
+        fs::write(dir.join("rebase-merge/head-name"), "refs/heads/main\n").unwrap();
+        fs::write(dir.join("rebase-merge/msgnum"), "3\n").unwrap();
+        fs::write(dir.join("rebase-merge/end"), "7\n").unwrap();
+        assert_eq!(decorate(), "main|REBASE 3/7");
+
+        fs::remove_dir_all(dir.join("rebase-merge")).unwrap();
+        fs::write(dir.join("MERGE_HEAD"), "0123\n").unwrap();
+        assert_eq!(decorate_branch("main".to_string(), dir), "main|MERGE");
+
+        fs::remove_file(dir.join("MERGE_HEAD")).unwrap();
+        assert_eq!(decorate_branch("main".to_string(), dir), "main");
+        assert_eq!(stash_count(dir), 0);
+
+        fs::create_dir_all(dir.join("logs/refs")).unwrap();
+        fs::write(dir.join("logs/refs/stash"), "a b\nc d\n").unwrap();
+        assert_eq!(stash_count(dir), 2);
+    }
+}
@@ -1,10 +1,18 @@
-use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{Duration, SystemTime};
 
-const CACHE_TTL_SECS: u64 = 300; // 5 minutes
+use crate::config::{ContributionsConfig, GithubConfig};
+use crate::data::{cache_dir, clock, refresh, write_atomic, STALE_MARKER};
+
+/// Hidden flag the statusline re-runs itself with to refresh the cache in
+/// the background.
+pub const REFRESH_FLAG: &str = "--refresh-contributions";
+
+/// A refresh that has held the lock this long is presumed dead. After a
+/// failed fetch the lock is left in place, so this also spaces out retries.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Debug)]
 struct Cache {
@@ -12,52 +20,80 @@ struct Cache {
     updated_at: SystemTime,
 }
 
-pub fn today_contributions() -> String {
-    if let Ok(val) = env::var("CC_CONTRIBUTIONS") {
-        if !val.is_empty() {
-            return format!("🌲 {}", val);
-        }
+impl Cache {
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        age(self.updated_at) < ttl
     }
+}
 
-    match get_contributions_cached() {
-        Some(count) => format!("🌲 {}", count),
-        None => "-".to_string(),
+/// Never waits on the network: an expired count is shown, marked, while a
+/// detached process fetches a new one for the next render.
+pub fn today_contributions(github: &GithubConfig, config: &ContributionsConfig) -> String {
+    if let Some(count) = &config.count {
+        return format!("🌲 {}", count);
     }
+
+    let Some(cache_path) = cache_file_path() else {
+        return "-".to_string();
+    };
+    cached_count(
+        &cache_path,
+        Duration::from_secs(github.cache_ttl),
+        start_refresh,
+    )
 }
 
-fn get_contributions_cached() -> Option<u32> {
-    let cache_path = cache_file_path()?;
+/// The count cached at `cache_path`, calling `refresh` when it is older
+/// than `ttl` or missing.
+fn cached_count(cache_path: &Path, ttl: Duration, refresh: impl FnOnce(&Path)) -> String {
+    match read_cache(cache_path) {
+        Some(cache) if cache.is_fresh(ttl) => format!("🌲 {}", cache.contributions),
+        Some(cache) => {
+            refresh(cache_path);
+            format!("🌲 {}{}", cache.contributions, STALE_MARKER)
+        }
+        None => {
+            refresh(cache_path);
+            "-".to_string()
+        }
+    }
+}
 
-    // Try to read from cache
-    if let Some(cache) = read_cache(&cache_path) {
-        let elapsed = SystemTime::now()
-            .duration_since(cache.updated_at)
-            .unwrap_or(Duration::from_secs(u64::MAX));
+/// Fetch and cache today's count; what the detached process runs.
+pub fn refresh(github: &GithubConfig) {
+    let Some(cache_path) = cache_file_path() else {
+        return;
+    };
 
-        if elapsed < Duration::from_secs(CACHE_TTL_SECS) {
-            return Some(cache.contributions);
-        }
+    if let Some(contributions) = fetch_today_contributions(github) {
+        write_cache(&cache_path, contributions);
+        let _ = fs::remove_file(lock_file_path(&cache_path));
     }
+}
 
-    // Cache miss or expired - fetch fresh data
-    let contributions = fetch_today_contributions()?;
-    write_cache(&cache_path, contributions);
-    Some(contributions)
+fn start_refresh(cache_path: &Path) {
+    refresh::spawn(
+        &lock_file_path(cache_path),
+        LOCK_TIMEOUT,
+        &[REFRESH_FLAG.to_string()],
+    );
+}
+
+fn age(time: SystemTime) -> Duration {
+    SystemTime::now()
+        .duration_since(time)
+        .unwrap_or(Duration::ZERO)
 }
 
 fn cache_file_path() -> Option<PathBuf> {
-    let cache_dir = dirs_cache_dir()?;
-    let app_cache = cache_dir.join("cc-sakura-line");
-    Some(app_cache.join("github_contributions.txt"))
+    Some(cache_dir()?.join("github_contributions.txt"))
 }
 
-fn dirs_cache_dir() -> Option<PathBuf> {
-    env::var("HOME")
-        .ok()
-        .map(|h| PathBuf::from(h).join(".cache"))
+fn lock_file_path(cache_path: &Path) -> PathBuf {
+    cache_path.with_extension("lock")
 }
 
-fn read_cache(path: &PathBuf) -> Option<Cache> {
+fn read_cache(path: &Path) -> Option<Cache> {
     let metadata = fs::metadata(path).ok()?;
     let modified = metadata.modified().ok()?;
     let content = fs::read_to_string(path).ok()?;
@@ -68,16 +104,13 @@ fn read_cache(path: &PathBuf) -> Option<Cache> {
     })
 }
 
-fn write_cache(path: &PathBuf, contributions: u32) {
-    if let Some(parent) = path.parent() {
-        let _ = fs::create_dir_all(parent);
-    }
-    let _ = fs::write(path, contributions.to_string());
+fn write_cache(path: &Path, contributions: u32) {
+    let _ = write_atomic(path, contributions.to_string());
 }
 
-fn fetch_today_contributions() -> Option<u32> {
-    let username = get_github_username()?;
-    let today = get_today_date();
+fn fetch_today_contributions(github: &GithubConfig) -> Option<u32> {
+    let username = get_github_username(github)?;
+    let today = clock::today().to_string();
 
     let query = format!(
         r#"{{
@@ -134,11 +167,9 @@ fn fetch_today_contributions() -> Option<u32> {
     Some(0)
 }
 
-fn get_github_username() -> Option<String> {
-    if let Ok(val) = env::var("CC_GITHUB_USER") {
-        if !val.is_empty() {
-            return Some(val);
-        }
+fn get_github_username(github: &GithubConfig) -> Option<String> {
+    if let Some(user) = &github.user {
+        return Some(user.clone());
     }
 
     let output = Command::new("gh")
@@ -158,12 +189,52 @@ fn get_github_username() -> Option<String> {
     }
 }
 
-fn get_today_date() -> String {
-    let output = Command::new("date")
-        .args(["+%Y-%m-%d"])
-        .output()
-        .ok()
-        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+#[cfg(test)]
+mod tests {
+    use super::{cached_count, write_cache};
+    use crate::data::git::fixture::Scratch;
+    use std::{
+        fs::File,
+        path::Path,
+        time::{Duration, SystemTime},
+    };
+
+    const TTL: Duration = Duration::from_secs(300);
+
+    /// The cell's text, and whether a refresh was started.
+    fn count(path: &Path) -> (String, bool) {
+        let mut refreshed = false;
+        let text = cached_count(path, TTL, |_| refreshed = true);
+        (text, refreshed)
+    }
 
-    output.unwrap_or_else(|| "1970-01-01".to_string())
+    #[test]
+    fn fresh_cache_is_shown_as_is() {
+        let scratch = Scratch::new("contributions-fresh");
+        let path = scratch.0.join("github_contributions.txt");
+        write_cache(&path, 7);
+        assert_eq!(count(&path), ("🌲 7".to_string(), false));
+    }
+
+    #[test]
+    fn expired_cache_is_marked_and_refreshed() {
+        let scratch = Scratch::new("contributions-expired");
+        let path = scratch.0.join("github_contributions.txt");
+        write_cache(&path, 7);
+        let old = SystemTime::now() - TTL - Duration::from_secs(1);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert_eq!(count(&path), ("🌲 7 ·".to_string(), true));
+    }
+
+    #[test]
+    fn missing_cache_is_a_dash_and_refreshed() {
+        let scratch = Scratch::new("contributions-missing");
+        let path = scratch.0.join("github_contributions.txt");
+        assert_eq!(count(&path), ("-".to_string(), true));
+    }
 }
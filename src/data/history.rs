@@ -0,0 +1,175 @@
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use crate::data::{data_dir, git::NO_REPO, session_cache, write_atomic, Sources};
+
+const FILE: &str = "history.jsonl";
+
+/// Session time alone is news once it has grown this much, so idle and
+/// read-only sessions still add up in `stats` without a line per render.
+const DURATION_STEP_MS: u64 = 60_000;
+
+/// One render that changed something, as a line of `history.jsonl`.
+/// Tokens, cost and duration are the session's running totals at that moment.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Record {
+    pub at: Option<Timestamp>,
+    pub session: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+}
+
+impl Record {
+    /// `None` without a session id, e.g. in preview mode. Git is taken as
+    /// far as it is known, without waiting for it.
+    pub fn from_sources(sources: &Sources<'_>) -> Option<Self> {
+        let input = sources.input;
+        let known = |value: &str| (value != "-" && value != NO_REPO).then(|| value.to_string());
+        let git = sources.git_now();
+        let window = &input.context_window;
+        let tokens = match (window.total_input_tokens, window.total_output_tokens) {
+            (None, None) => None,
+            (input, output) => Some(input.unwrap_or(0) + output.unwrap_or(0)),
+        };
+
+        Some(Self {
+            at: Some(Timestamp::now()),
+            session: input.session_id.clone()?,
+            model: input
+                .model
+                .display_name
+                .clone()
+                .or_else(|| input.model.id.clone()),
+            repo: git.and_then(|git| known(&git.repository)),
+            branch: git.and_then(|git| known(&git.branch)),
+            tokens,
+            cost_usd: input.cost.total_cost_usd,
+            duration_ms: input.duration_ms(),
+        })
+    }
+
+    /// Whether this says anything `last` did not. Session time is news a
+    /// minute at a time, and git that is not known yet does not undo what was.
+    fn is_news(&self, last: &Record) -> bool {
+        let moved = |now: &Option<String>, then: &Option<String>| now.is_some() && now != then;
+        let ran = match (self.duration_ms, last.duration_ms) {
+            (Some(now), Some(then)) => now >= then.saturating_add(DURATION_STEP_MS),
+            (now, then) => now.is_some() && then.is_none(),
+        };
+        self.session != last.session
+            || ran
+            || self.model != last.model
+            || self.tokens != last.tokens
+            || self.cost_usd != last.cost_usd
+            || moved(&self.repo, &last.repo)
+            || moved(&self.branch, &last.branch)
+    }
+}
+
+/// Add a record to the end of the store, unless nothing changed since the
+/// session's last one. The line goes out in a single write, so concurrent
+/// sessions do not interleave.
+pub fn append(record: &Record) {
+    let Some(path) = history_path() else {
+        return;
+    };
+    let last_path = session_cache("history", &record.session);
+    let last = last_path
+        .as_deref()
+        .and_then(|path| serde_json::from_str::<Record>(&fs::read_to_string(path).ok()?).ok());
+    if last.is_some_and(|last| !record.is_news(&last)) {
+        return;
+    }
+    let Ok(mut line) = serde_json::to_string(record) else {
+        return;
+    };
+    line.push('\n');
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) else {
+        return;
+    };
+    if file.write_all(line.as_bytes()).is_err() {
+        return;
+    }
+
+    if let Some(last_path) = last_path {
+        let _ = write_atomic(&last_path, line.trim_end());
+    }
+}
+
+/// Every stored record, oldest first; unreadable lines are skipped.
+pub fn read_all() -> Vec<Record> {
+    let Some(text) = history_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn history_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Record;
+
+    #[test]
+    fn only_changes_are_news() {
+        let last = Record {
+            at: Some("2024-03-10T12:00:00Z".parse().unwrap()),
+            session: "a".to_string(),
+            repo: Some("sakura".to_string()),
+            tokens: Some(1_000),
+            cost_usd: Some(0.5),
+            duration_ms: Some(60_000),
+            ..Record::default()
+        };
+        let later = Record {
+            at: Some("2024-03-10T12:00:05Z".parse().unwrap()),
+            duration_ms: Some(65_000),
+            repo: None,
+            ..last.clone()
+        };
+        assert!(!later.is_news(&last));
+
+        let spent = Record {
+            tokens: Some(1_400),
+            cost_usd: Some(0.6),
+            ..later.clone()
+        };
+        assert!(spent.is_news(&last));
+
+        let moved = Record {
+            repo: Some("other".to_string()),
+            ..later.clone()
+        };
+        assert!(moved.is_news(&last));
+
+        let idle = Record {
+            duration_ms: Some(120_000),
+            ..later
+        };
+        assert!(idle.is_news(&last));
+    }
+}
@@ -0,0 +1,264 @@
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::Value;
+use std::io::{self, Read};
+
+/// The JSON Claude Code pipes to statusline commands. Every field is
+/// optional and unknown fields are ignored. A field of the wrong type reads
+/// as missing instead of failing the whole payload, and numbers sent as
+/// strings (or the reverse) are accepted.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StatusInput {
+    #[serde(deserialize_with = "lenient")]
+    pub model: Model,
+    #[serde(deserialize_with = "lenient")]
+    pub workspace: Workspace,
+    #[serde(deserialize_with = "text")]
+    pub session_id: Option<String>,
+    #[serde(deserialize_with = "text")]
+    pub transcript_path: Option<String>,
+    #[serde(deserialize_with = "text")]
+    pub cwd: Option<String>,
+    #[serde(deserialize_with = "text")]
+    pub version: Option<String>,
+    #[serde(deserialize_with = "lenient")]
+    pub output_style: OutputStyle,
+    #[serde(deserialize_with = "lenient")]
+    pub cost: Cost,
+    #[serde(deserialize_with = "lenient")]
+    pub context_window: ContextWindow,
+    /// Older payloads put the session's duration here instead of `cost`.
+    #[serde(deserialize_with = "lenient")]
+    pub session: Session,
+    #[serde(deserialize_with = "count")]
+    pub total_duration_ms: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub elapsed_ms: Option<u64>,
+}
+
+impl StatusInput {
+    /// How long the session has run: `cost.total_duration_ms`, else the
+    /// first of the fields older payloads used.
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.cost
+            .total_duration_ms
+            .or(self.session.total_duration_ms)
+            .or(self.session.duration_ms)
+            .or(self.total_duration_ms)
+            .or(self.elapsed_ms)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Model {
+    #[serde(deserialize_with = "text")]
+    pub id: Option<String>,
+    #[serde(deserialize_with = "text")]
+    pub display_name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    #[serde(deserialize_with = "text")]
+    pub current_dir: Option<String>,
+    #[serde(deserialize_with = "text")]
+    pub project_dir: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    #[serde(deserialize_with = "count")]
+    pub total_duration_ms: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct OutputStyle {
+    #[serde(deserialize_with = "text")]
+    pub name: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Cost {
+    #[serde(deserialize_with = "amount")]
+    pub total_cost_usd: Option<f64>,
+    #[serde(deserialize_with = "count")]
+    pub total_duration_ms: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub total_api_duration_ms: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub total_lines_added: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub total_lines_removed: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ContextWindow {
+    #[serde(deserialize_with = "count")]
+    pub context_window_size: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub total_input_tokens: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub total_output_tokens: Option<u64>,
+    #[serde(deserialize_with = "lenient")]
+    pub current_usage: Usage,
+}
+
+/// Tokens in the context window as of the last response.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    #[serde(deserialize_with = "count")]
+    pub input_tokens: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub output_tokens: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub cache_creation_input_tokens: Option<u64>,
+    #[serde(deserialize_with = "count")]
+    pub cache_read_input_tokens: Option<u64>,
+}
+
+/// Parse stdin; empty or malformed input reads as an empty payload.
+pub fn read_stdin() -> StatusInput {
+    let mut input = String::new();
+    if io::stdin().read_to_string(&mut input).is_err() {
+        return StatusInput::default();
+    }
+    parse(&input)
+}
+
+pub fn parse(text: &str) -> StatusInput {
+    serde_json::from_str(text.trim()).unwrap_or_default()
+}
+
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_default())
+}
+
+fn text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => Some(s),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    })
+}
+
+fn count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    })
+}
+
+fn amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn fixture_1_0_71_has_no_cost_or_context() {
+        let input = parse(include_str!("../../tests/fixtures/claude-code-1.0.71.json"));
+        assert_eq!(input.model.display_name.as_deref(), Some("Opus"));
+        assert_eq!(input.version.as_deref(), Some("1.0.71"));
+        assert_eq!(
+            input.workspace.current_dir.as_deref(),
+            Some("/home/sakura/cc-sakura-line")
+        );
+        assert_eq!(input.cost.total_duration_ms, None);
+        assert_eq!(input.context_window.context_window_size, None);
+    }
+
+    #[test]
+    fn fixture_1_0_88_adds_cost_and_output_style() {
+        let input = parse(include_str!("../../tests/fixtures/claude-code-1.0.88.json"));
+        assert_eq!(input.output_style.name.as_deref(), Some("default"));
+        assert_eq!(input.cost.total_cost_usd, Some(0.4213));
+        assert_eq!(input.cost.total_duration_ms, Some(1_932_000));
+        assert_eq!(input.cost.total_api_duration_ms, Some(412_000));
+        assert_eq!(input.cost.total_lines_added, Some(156));
+        assert_eq!(input.cost.total_lines_removed, Some(23));
+    }
+
+    #[test]
+    fn fixture_2_0_65_adds_context_window() {
+        let input = parse(include_str!("../../tests/fixtures/claude-code-2.0.65.json"));
+        let window = &input.context_window;
+        assert_eq!(window.context_window_size, Some(200_000));
+        assert_eq!(window.total_input_tokens, Some(81_240));
+        assert_eq!(window.current_usage.input_tokens, Some(1_200));
+        assert_eq!(window.current_usage.output_tokens, Some(830));
+        assert_eq!(
+            window.current_usage.cache_creation_input_tokens,
+            Some(4_100)
+        );
+        assert_eq!(window.current_usage.cache_read_input_tokens, Some(38_900));
+    }
+
+    #[test]
+    fn tolerates_odd_types_and_unknown_fields() {
+        let input = parse(
+            r#"{
+              "model": "opus",
+              "version": 2,
+              "cost": { "total_duration_ms": "60000", "total_cost_usd": null },
+              "context_window": { "context_window_size": 200000, "current_usage": [] },
+              "something_new": { "nested": true }
+            }"#,
+        );
+        assert_eq!(input.model.display_name, None);
+        assert_eq!(input.version.as_deref(), Some("2"));
+        assert_eq!(input.cost.total_duration_ms, Some(60_000));
+        assert_eq!(input.cost.total_cost_usd, None);
+        assert_eq!(input.context_window.context_window_size, Some(200_000));
+        assert_eq!(input.context_window.current_usage.input_tokens, None);
+    }
+
+    #[test]
+    fn duration_falls_back_to_older_fields() {
+        let duration = |text: &str| parse(text).duration_ms();
+        assert_eq!(
+            duration(r#"{"cost":{"total_duration_ms":1},"session":{"total_duration_ms":2}}"#),
+            Some(1)
+        );
+        assert_eq!(
+            duration(r#"{"session":{"total_duration_ms":2,"duration_ms":3}}"#),
+            Some(2)
+        );
+        assert_eq!(
+            duration(r#"{"session":{"duration_ms":3},"total_duration_ms":4}"#),
+            Some(3)
+        );
+        assert_eq!(
+            duration(r#"{"total_duration_ms":"4","elapsed_ms":5}"#),
+            Some(4)
+        );
+        assert_eq!(duration(r#"{"elapsed_ms":5}"#), Some(5));
+        assert_eq!(duration("{}"), None);
+    }
+
+    #[test]
+    fn malformed_input_is_empty() {
+        assert!(parse("").model.id.is_none());
+        assert!(parse("not json").model.id.is_none());
+    }
+}
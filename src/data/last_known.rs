@@ -0,0 +1,52 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use super::{cache_dir, lockfile, write_atomic};
+
+const FILE: &str = "last_known.json";
+
+/// How long a save waits for another thread or statusline process to
+/// finish its own read-modify-write of the shared file.
+const LOCK_WAIT: Duration = Duration::from_millis(200);
+const LOCK_STALE: Duration = Duration::from_secs(10);
+
+/// The value last saved under `key`, if any.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    let mut entries = read(&path()?);
+    serde_json::from_value(entries.remove(key)?).ok()
+}
+
+/// Save `value` under `key`, keeping every other key; skipped when the
+/// file stays locked.
+pub fn save<T: Serialize>(key: &str, value: &T) {
+    let Some(path) = path() else {
+        return;
+    };
+    let Ok(value) = serde_json::to_value(value) else {
+        return;
+    };
+    let Some(_guard) = lockfile::take(&path.with_extension("lock"), LOCK_STALE, LOCK_WAIT) else {
+        return;
+    };
+
+    let mut entries = read(&path);
+    entries.insert(key.to_string(), value);
+
+    let _ = write_atomic(&path, Value::Object(entries).to_string());
+}
+
+fn path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join(FILE))
+}
+
+fn read(path: &Path) -> Map<String, Value> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
@@ -0,0 +1,161 @@
+use jiff::{civil::Date, ToSpan};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::data::{clock, data_dir, input::StatusInput, lockfile, write_atomic};
+
+const FILE: &str = "ledger.json";
+
+/// How long a render waits for another session to finish writing.
+const LOCK_WAIT: Duration = Duration::from_millis(200);
+const LOCK_STALE: Duration = Duration::from_secs(10);
+
+/// Sessions idle this long are dropped; enough to cover the previous month.
+const KEEP_DAYS: i64 = 62;
+
+/// Latest cost of every session, with each increase booked to the day it
+/// was seen, so a session running past midnight splits across both days.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Ledger {
+    sessions: BTreeMap<String, SessionSpend>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionSpend {
+    cost_usd: f64,
+    by_day: BTreeMap<Date, f64>,
+}
+
+/// Spend across all recorded sessions, in USD.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Spend {
+    pub today: f64,
+    pub month: f64,
+}
+
+/// Book this render's session cost, then total up today and this month.
+/// When the ledger stays locked, the totals are read without recording.
+pub fn record(input: &StatusInput) -> Spend {
+    let today = clock::today();
+    let Some(path) = ledger_path() else {
+        return Spend::default();
+    };
+
+    let lock = path.with_extension("lock");
+    let guard = lockfile::take(&lock, LOCK_STALE, LOCK_WAIT);
+    let mut ledger = read(&path);
+
+    if let (Some(_), Some(id), Some(cost)) = (&guard, &input.session_id, input.cost.total_cost_usd)
+    {
+        if ledger.update(id, cost, today) {
+            ledger.prune(today);
+            write(&path, &ledger);
+        }
+    }
+
+    ledger.spend(today)
+}
+
+impl Ledger {
+    /// Returns whether anything changed.
+    fn update(&mut self, session_id: &str, cost_usd: f64, today: Date) -> bool {
+        let session = self.sessions.entry(session_id.to_string()).or_default();
+        let delta = cost_usd - session.cost_usd;
+        if delta == 0.0 {
+            return false;
+        }
+        // A lower cost means the session restarted its count; nothing new
+        // was spent.
+        if delta > 0.0 {
+            *session.by_day.entry(today).or_default() += delta;
+        }
+        session.cost_usd = cost_usd;
+        true
+    }
+
+    fn prune(&mut self, today: Date) {
+        let Ok(cutoff) = today.checked_sub(KEEP_DAYS.days()) else {
+            return;
+        };
+        self.sessions.retain(|_, session| {
+            session
+                .by_day
+                .keys()
+                .next_back()
+                .is_some_and(|last| *last >= cutoff)
+        });
+    }
+
+    fn spend(&self, today: Date) -> Spend {
+        let mut spend = Spend::default();
+        for (day, amount) in self.sessions.values().flat_map(|s| &s.by_day) {
+            if *day == today {
+                spend.today += amount;
+            }
+            if (day.year(), day.month()) == (today.year(), today.month()) {
+                spend.month += amount;
+            }
+        }
+        spend
+    }
+}
+
+fn ledger_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join(FILE))
+}
+
+fn read(path: &Path) -> Ledger {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn write(path: &Path, ledger: &Ledger) {
+    if let Ok(text) = serde_json::to_string(ledger) {
+        let _ = write_atomic(path, text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ledger, Spend};
+    use jiff::civil::date;
+
+    #[test]
+    fn books_increases_to_the_day_seen() {
+        let mut ledger = Ledger::default();
+        let day1 = date(2024, 3, 10);
+        let day2 = date(2024, 3, 11);
+
+        assert!(ledger.update("a", 1.5, day1));
+        assert!(!ledger.update("a", 1.5, day1));
+        assert!(ledger.update("a", 2.0, day2));
+        assert!(ledger.update("b", 0.25, day2));
+
+        assert_eq!(
+            ledger.spend(day2),
+            Spend {
+                today: 0.75,
+                month: 2.25
+            }
+        );
+    }
+
+    #[test]
+    fn month_resets_and_old_sessions_go() {
+        let mut ledger = Ledger::default();
+        ledger.update("old", 5.0, date(2024, 1, 5));
+        ledger.update("last-month", 2.0, date(2024, 2, 28));
+        ledger.update("now", 1.0, date(2024, 3, 1));
+
+        ledger.prune(date(2024, 3, 10));
+        assert!(!ledger.sessions.contains_key("old"));
+        assert_eq!(ledger.spend(date(2024, 3, 10)).month, 1.0);
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    fs::{self, OpenOptions},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Lock files shared by statuslines running at once. A lock older than
+/// `stale` is taken to belong to a process that died holding it.
+pub fn try_take(path: &Path, stale: Duration) -> bool {
+    if let Ok(modified) = fs::metadata(path).and_then(|meta| meta.modified()) {
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or(Duration::ZERO);
+        if age < stale {
+            return false;
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .is_ok()
+}
+
+/// Take the lock, retrying for up to `wait`; released when the guard drops.
+pub fn take(path: &Path, stale: Duration, wait: Duration) -> Option<Guard> {
+    let deadline = Instant::now() + wait;
+    loop {
+        if try_take(path, stale) {
+            return Some(Guard {
+                path: path.to_path_buf(),
+            });
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+pub struct Guard {
+    path: PathBuf,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{take, try_take};
+    use std::{env, process, time::Duration};
+
+    #[test]
+    fn lock_is_taken_once_until_released() {
+        let path = env::temp_dir().join(format!("cc-sakura-line-test-{}.lock", process::id()));
+        let stale = Duration::from_secs(60);
+
+        let guard = take(&path, stale, Duration::ZERO).unwrap();
+        assert!(!try_take(&path, stale));
+        drop(guard);
+        assert!(take(&path, stale, Duration::ZERO).is_some());
+    }
+}
@@ -1,73 +1,276 @@
-mod claude;
-mod clock;
-mod context;
-mod git;
-mod github;
-mod session;
-
-use serde_json::Value;
-use std::time::Instant;
-
-pub use claude::read_stdin_json;
-
-#[derive(Clone, Debug)]
-pub struct Snapshot {
-    // Row 1: Claude
-    pub model: String,
-    pub version: String,
-    pub contributions: String,
-    pub session_clock: String,
-    // Row 2: Git
-    pub repository: String,
-    pub branch: String,
-    pub git_changes: String,
-    pub ahead_behind: String,
-    // Row 3: Context
-    pub context: String,
-    pub context_remaining: String,
-    pub now_clock: String,
+pub mod claude;
+pub mod clock;
+pub mod context;
+pub mod cost;
+mod fetch;
+pub mod git;
+pub mod github;
+pub mod history;
+pub mod input;
+mod last_known;
+pub mod ledger;
+mod lockfile;
+mod refresh;
+pub mod session;
+pub mod transcript;
+pub mod workspace;
+
+use std::{
+    cell::OnceCell,
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use crate::config::Config;
+use fetch::Fetch;
+use input::StatusInput;
+
+pub use input::read_stdin;
+
+/// Shown in place of a background source that is not back yet and has no
+/// last known value.
+pub const PENDING: &str = "…";
+
+/// Appended to a value older than it should be, e.g. a last known one.
+pub const STALE_MARKER: &str = " ·";
+
+/// Hidden flag the statusline re-runs itself with to finish a git read that
+/// missed its deadline; the value is the `git::Query` as JSON.
+pub const REFRESH_GIT_FLAG: &str = "--refresh-git=";
+
+/// Lets a detached git refresh run this long before another may start.
+const GIT_REFRESH_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What preview mode collects from, having no payload of its own.
+static NO_INPUT: LazyLock<StatusInput> = LazyLock::new(StatusInput::default);
+
+/// Sources that spawn processes or hit the network. They are started together
+/// on their own threads, each with a time budget from `[timeouts]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Git,
+    Github,
+}
+
+/// What segments collect from: the Claude Code payload, the config, and
+/// sources shared by several segments, fetched at most once per render.
+pub struct Sources<'a> {
+    pub input: &'a StatusInput,
+    pub config: &'a Config,
+    /// Set in preview mode, where the session clock counts from launch.
+    pub started_at: Option<Instant>,
+    /// The directory git is read from.
+    dir: PathBuf,
+    git: Fetch<git::GitInfo>,
+    contributions: Fetch<String>,
+    context: OnceCell<context::ContextInfo>,
+    spend: OnceCell<ledger::Spend>,
+    transcript: OnceCell<Option<transcript::TranscriptInfo>>,
+}
+
+impl<'a> Sources<'a> {
+    pub fn from_input(input: &'a StatusInput, config: &'a Config) -> Self {
+        let timeouts = &config.timeouts;
+        let dir = workspace::current_dir(input).unwrap_or_else(|| PathBuf::from("."));
+
+        Self {
+            input,
+            config,
+            started_at: None,
+            git: Fetch::new(Duration::from_millis(timeouts.git), Some(git_key(&dir))),
+            dir,
+            contributions: Fetch::new(
+                Duration::from_millis(timeouts.github),
+                Some("contributions".to_string()),
+            ),
+            context: OnceCell::new(),
+            spend: OnceCell::new(),
+            transcript: OnceCell::new(),
+        }
+    }
+
+    pub fn preview(started_at: Instant, config: &'a Config) -> Self {
+        Self {
+            started_at: Some(started_at),
+            ..Self::from_input(&NO_INPUT, config)
+        }
+    }
+
+    /// Kick off a background source so it runs alongside the others.
+    pub fn start(&self, source: Source) {
+        match source {
+            Source::Git => self.git.start(self.fetch_git()),
+            Source::Github => self.contributions.start(self.fetch_contributions()),
+        }
+    }
+
+    /// Whether `source` was read from its last known value.
+    pub fn is_stale(&self, source: Source) -> bool {
+        match source {
+            Source::Git => self.git.is_stale(),
+            Source::Github => self.contributions.is_stale(),
+        }
+    }
+
+    /// Called once the line is out. A git read that missed its deadline is
+    /// left to a detached process, so the next render has a newer value to
+    /// fall back to; it is pointed at the same workspace so it loads the
+    /// same project config. Contributions refresh themselves.
+    pub fn finish(&self) {
+        if !self.git.is_late() {
+            return;
+        }
+        let (Some(lock_path), Ok(query)) = (
+            cache_dir().map(|dir| dir.join("refresh").join(lock_name(&git_key(&self.dir)))),
+            serde_json::to_string(&self.git_query()),
+        ) else {
+            return;
+        };
+        refresh::spawn(
+            &lock_path,
+            GIT_REFRESH_TIMEOUT,
+            &[
+                format!("--cwd={}", self.dir.display()),
+                format!("{}{}", REFRESH_GIT_FLAG, query),
+            ],
+        );
+    }
+
+    pub fn git(&self) -> Option<&git::GitInfo> {
+        self.git.get(self.fetch_git())
+    }
+
+    /// Git as far as it is known without waiting, for bookkeeping once the
+    /// line is out.
+    pub fn git_now(&self) -> Option<&git::GitInfo> {
+        self.git.now()
+    }
+
+    pub fn contributions(&self) -> Option<&str> {
+        self.contributions
+            .get(self.fetch_contributions())
+            .map(String::as_str)
+    }
+
+    pub fn context(&self) -> &context::ContextInfo {
+        self.context
+            .get_or_init(|| context::from_input(self.input, &self.config.segments.context))
+    }
+
+    /// Today's and this month's spend, after recording this session's cost.
+    pub fn spend(&self) -> &ledger::Spend {
+        self.spend.get_or_init(|| ledger::record(self.input))
+    }
+
+    pub fn transcript(&self) -> Option<&transcript::TranscriptInfo> {
+        self.transcript
+            .get_or_init(|| transcript::from_input(self.input))
+            .as_ref()
+    }
+
+    fn git_query(&self) -> git::Query {
+        git::Query {
+            dir: self.dir.clone(),
+            session_start: session::started_at(self.input),
+            tag_pattern: self.config.git.tag_pattern.clone(),
+        }
+    }
+
+    fn fetch_git(&self) -> impl FnOnce() -> git::GitInfo + Send + 'static {
+        let backend = self.config.git.backend;
+        let query = self.git_query();
+        move || git::snapshot(backend, &query)
+    }
+
+    fn fetch_contributions(&self) -> impl FnOnce() -> String + Send + 'static {
+        let github = self.config.github.clone();
+        let contributions = self.config.segments.contributions.clone();
+        move || github::today_contributions(&github, &contributions)
+    }
+}
+
+/// Read git for a `REFRESH_GIT_FLAG` query and save it as the last known
+/// value; what the detached process runs.
+pub fn refresh_git(config: &Config, query: &str) {
+    let Ok(query) = serde_json::from_str::<git::Query>(query) else {
+        return;
+    };
+    let key = git_key(&query.dir);
+    let info = git::snapshot(config.git.backend, &query);
+    last_known::save(&key, &info);
+    if let Some(dir) = cache_dir() {
+        let _ = fs::remove_file(dir.join("refresh").join(lock_name(&key)));
+    }
+}
+
+fn git_key(dir: &Path) -> String {
+    format!("git:{}", dir.display())
+}
+
+/// A file name unique to `key`: its 64-bit FNV-1a hash, which unlike
+/// `DefaultHasher` is the same in every build.
+fn lock_name(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}.lock", hash)
+}
+
+/// `~/.cache/cc-sakura-line`, shared by everything cached between runs.
+pub fn cache_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".cache").join("cc-sakura-line"))
+}
+
+/// A per-session file under `cache_dir()/<kind>`; `None` for a session id
+/// with nothing usable in a file name.
+pub fn session_cache(kind: &str, session_id: &str) -> Option<PathBuf> {
+    let name: String = session_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if name.is_empty() {
+        return None;
+    }
+    Some(cache_dir()?.join(kind).join(format!("{}.json", name)))
 }
 
-pub fn collect_from_input(input: Option<&Value>) -> Snapshot {
-    let git = git::snapshot();
-    let context = context::from_input(input);
-
-    Snapshot {
-        // Row 1: Claude
-        model: claude::model(input),
-        version: claude::version(input).unwrap_or_else(|| "-".to_string()),
-        contributions: github::today_contributions(),
-        session_clock: session::from_input(input),
-        // Row 2: Git
-        repository: git.repository,
-        branch: git.branch,
-        git_changes: git.changes,
-        ahead_behind: git.ahead_behind,
-        // Row 3: Context
-        context: context.context,
-        context_remaining: context.remaining,
-        now_clock: clock::now_hms(),
+/// Replace `path` with `contents`, creating its directory if needed. The
+/// bytes go to a temporary file renamed into place, so other statusline
+/// processes never read half a file.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
     }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".{}", std::process::id()));
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
 }
 
-pub fn collect_preview(started_at: Instant) -> Snapshot {
-    let git = git::snapshot();
-    let context = context::from_input(None);
-
-    Snapshot {
-        // Row 1: Claude
-        model: claude::model(None),
-        version: claude::version(None).unwrap_or_else(|| "-".to_string()),
-        contributions: github::today_contributions(),
-        session_clock: session::clock(started_at),
-        // Row 2: Git
-        repository: git.repository,
-        branch: git.branch,
-        git_changes: git.changes,
-        ahead_behind: git.ahead_behind,
-        // Row 3: Context
-        context: context.context,
-        context_remaining: context.remaining,
-        now_clock: clock::now_hms(),
+/// `$XDG_DATA_HOME/cc-sakura-line` (default `~/.local/share/cc-sakura-line`),
+/// for records that should outlive a cache cleanup.
+pub fn data_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?)
+            .join(".local")
+            .join("share"),
+    };
+    Some(base.join("cc-sakura-line"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::lock_name;
+
+    #[test]
+    fn lock_names_keep_keys_apart() {
+        assert_ne!(lock_name("git:/a/b-c"), lock_name("git:/a/b_c"));
+        assert_eq!(lock_name("git:/a/b-c"), lock_name("git:/a/b-c"));
+        assert_eq!(lock_name(""), "cbf29ce484222325.lock");
     }
 }
@@ -0,0 +1,45 @@
+use std::{
+    env, fs,
+    path::Path,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use crate::data::lockfile;
+
+/// Re-run this binary with the same arguments plus `flags`, detached from our
+/// stdout so Claude Code does not wait for it. The lock at `lock_path` keeps
+/// one refresh running at a time; the refresh removes it once it succeeds,
+/// and a lock older than `stale` is taken over.
+pub fn spawn(lock_path: &Path, stale: Duration, flags: &[String]) {
+    if !lockfile::try_take(lock_path, stale) {
+        return;
+    }
+
+    let Ok(exe) = env::current_exe() else {
+        let _ = fs::remove_file(lock_path);
+        return;
+    };
+
+    let mut command = Command::new(exe);
+    command
+        .args(
+            env::args()
+                .skip(1)
+                .filter(|arg| !arg.starts_with("--refresh-")),
+        )
+        .args(flags)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    if command.spawn().is_err() {
+        let _ = fs::remove_file(lock_path);
+    }
+}
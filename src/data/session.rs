@@ -1,32 +1,39 @@
-use serde_json::Value;
+use jiff::Timestamp;
 use std::time::Instant;
 
-use crate::data::claude;
+use crate::data::input::StatusInput;
 
 pub fn clock(started_at: Instant) -> String {
     let elapsed = started_at.elapsed();
     format_duration(elapsed.as_secs())
 }
 
-pub fn from_input(input: Option<&Value>) -> String {
-    let root = match input {
-        Some(root) => root,
-        None => return "-".to_string(),
-    };
-
-    let ms = claude::lookup_u64(root, &["cost", "total_duration_ms"])
-        .or_else(|| claude::lookup_u64(root, &["session", "total_duration_ms"]))
-        .or_else(|| claude::lookup_u64(root, &["session", "duration_ms"]))
-        .or_else(|| claude::lookup_u64(root, &["total_duration_ms"]))
-        .or_else(|| claude::lookup_u64(root, &["elapsed_ms"]));
-
-    match ms {
+pub fn from_input(input: &StatusInput) -> String {
+    match input.duration_ms() {
         Some(ms) => format_duration(ms / 1000),
         None => "-".to_string(),
     }
 }
 
-fn format_duration(total_secs: u64) -> String {
+/// When the session began, in seconds since the Unix epoch, worked back
+/// from its duration.
+pub fn started_at(input: &StatusInput) -> Option<i64> {
+    let ms = input.duration_ms()?;
+    Some(Timestamp::now().as_second() - (ms / 1000) as i64)
+}
+
+/// How long ago a Unix time was: `just now`, `4m ago`, `5h ago`, `3d ago`.
+pub fn format_age(time: i64) -> String {
+    let secs = (Timestamp::now().as_second() - time).max(0);
+    match secs {
+        0..60 => "just now".to_string(),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+pub fn format_duration(total_secs: u64) -> String {
     if total_secs < 60 {
         return "<1m".to_string();
     }
@@ -47,7 +54,8 @@ fn format_duration(total_secs: u64) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::format_duration;
+    use super::{format_age, format_duration};
+    use jiff::Timestamp;
 
     #[test]
     fn format_duration_humanized() {
@@ -58,4 +66,13 @@ mod tests {
         assert_eq!(format_duration(3600), "1h");
         assert_eq!(format_duration(19920), "5h32m");
     }
+
+    #[test]
+    fn format_age_relative() {
+        let now = Timestamp::now().as_second();
+        assert_eq!(format_age(now + 5), "just now");
+        assert_eq!(format_age(now - 240), "4m ago");
+        assert_eq!(format_age(now - 5 * 3_600 - 10), "5h ago");
+        assert_eq!(format_age(now - 3 * 86_400), "3d ago");
+    }
 }
@@ -0,0 +1,187 @@
+use jiff::Timestamp;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::data::{input::StatusInput, session_cache, write_atomic};
+
+/// What the session transcript says so far.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptInfo {
+    /// Prompts typed by the user; tool results fed back are not turns.
+    pub turns: u32,
+    pub last_tool: Option<String>,
+    pub last_reply_at: Option<Timestamp>,
+}
+
+/// Progress through one transcript, kept between renders so each render
+/// only parses the lines added since the last one.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct State {
+    path: String,
+    offset: u64,
+    info: TranscriptInfo,
+}
+
+/// `None` when the payload names no transcript or it cannot be read.
+pub fn from_input(input: &StatusInput) -> Option<TranscriptInfo> {
+    let path = input.transcript_path.as_deref()?;
+    let state_path = input
+        .session_id
+        .as_deref()
+        .and_then(|id| session_cache("transcripts", id));
+
+    let mut state = state_path
+        .as_deref()
+        .and_then(load)
+        .filter(|state| state.path == path)
+        .unwrap_or_else(|| State {
+            path: path.to_string(),
+            ..State::default()
+        });
+
+    let before = state.offset;
+    advance(&mut state, Path::new(path)).ok()?;
+    if let Some(state_path) = state_path.filter(|_| state.offset != before) {
+        save(&state_path, &state);
+    }
+    Some(state.info)
+}
+
+/// Parse the complete lines written since `state.offset`. A transcript
+/// shorter than the offset was replaced, so it is read again from the start.
+fn advance(state: &mut State, path: &Path) -> std::io::Result<()> {
+    let mut file = File::open(path)?;
+    if file.metadata()?.len() < state.offset {
+        state.offset = 0;
+        state.info = TranscriptInfo::default();
+    }
+
+    file.seek(SeekFrom::Start(state.offset))?;
+    let mut added = Vec::new();
+    file.read_to_end(&mut added)?;
+
+    // A line still being written is left for the next render.
+    let Some(end) = added.iter().rposition(|byte| *byte == b'\n') else {
+        return Ok(());
+    };
+    for line in String::from_utf8_lossy(&added[..end]).lines() {
+        apply(&mut state.info, line);
+    }
+    state.offset += end as u64 + 1;
+    Ok(())
+}
+
+fn apply(info: &mut TranscriptInfo, line: &str) {
+    let Ok(entry) = serde_json::from_str::<Value>(line) else {
+        return;
+    };
+    // Subagent traffic and injected context are not part of the
+    // conversation with the user.
+    let flag = |key: &str| entry.get(key).and_then(Value::as_bool) == Some(true);
+    if flag("isSidechain") || flag("isMeta") {
+        return;
+    }
+
+    let content = entry.pointer("/message/content");
+    match entry.get("type").and_then(Value::as_str) {
+        Some("user") => {
+            let typed = match content {
+                Some(Value::String(_)) => true,
+                Some(Value::Array(blocks)) => {
+                    blocks.iter().any(|block| block_type(block) == "text")
+                }
+                _ => false,
+            };
+            if typed {
+                info.turns += 1;
+            }
+        }
+        Some("assistant") => {
+            if let Some(at) = entry
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .and_then(|at| at.parse().ok())
+            {
+                info.last_reply_at = Some(at);
+            }
+            let mut tools = content
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|block| block_type(block) == "tool_use")
+                .filter_map(|block| block.get("name").and_then(Value::as_str));
+            if let Some(tool) = tools.next_back() {
+                info.last_tool = Some(tool.to_string());
+            }
+        }
+        _ => {}
+    }
+}
+
+fn block_type(block: &Value) -> &str {
+    block.get("type").and_then(Value::as_str).unwrap_or("")
+}
+
+fn load(path: &Path) -> Option<State> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn save(path: &Path, state: &State) {
+    if let Ok(text) = serde_json::to_string(state) {
+        let _ = write_atomic(path, text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{advance, State};
+    use std::{fs, io::Write};
+
+    const LINES: &str = r#"{"type":"user","message":{"role":"user","content":"fix the tests"}}
+{"type":"assistant","timestamp":"2024-03-10T12:00:00Z","message":{"content":[{"type":"text","text":"On it"},{"type":"tool_use","name":"Read","input":{}}]}}
+{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"1","content":"..."}]}}
+{"type":"assistant","timestamp":"2024-03-10T12:00:05Z","message":{"content":[{"type":"tool_use","name":"Edit","input":{}}]}}
+{"type":"user","isSidechain":true,"message":{"content":"subagent prompt"}}
+"#;
+
+    #[test]
+    fn reads_only_new_complete_lines() {
+        let path =
+            std::env::temp_dir().join(format!("cc-sakura-transcript-{}", std::process::id()));
+        fs::write(&path, LINES).unwrap();
+
+        let mut state = State::default();
+        advance(&mut state, &path).unwrap();
+        assert_eq!(state.info.turns, 1);
+        assert_eq!(state.info.last_tool.as_deref(), Some("Edit"));
+        assert_eq!(
+            state.info.last_reply_at.map(|at| at.to_string()).as_deref(),
+            Some("2024-03-10T12:00:05Z")
+        );
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        let added = concat!(
+            r#"{"type":"user","message":{"content":[{"type":"text","text":"now docs"}]}}"#,
+            "\n",
+            r#"{"type":"user","mess"#,
+        );
+        file.write_all(added.as_bytes()).unwrap();
+        advance(&mut state, &path).unwrap();
+        assert_eq!(state.info.turns, 2);
+
+        // The half-written line is not consumed.
+        let offset = state.offset;
+        advance(&mut state, &path).unwrap();
+        assert_eq!(state.offset, offset);
+        assert_eq!(state.info.turns, 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+use crate::data::input::StatusInput;
+
+/// Claude's working directory, relative to the project root: `src/data`,
+/// or `.` at the root. Outside any project it is the full path, with the
+/// home directory as `~`.
+pub fn directory(input: &StatusInput) -> String {
+    let Some(current) = current_dir(input) else {
+        return "-".to_string();
+    };
+
+    let relative = project_root(input, &current)
+        .and_then(|root| current.strip_prefix(root).ok().map(Path::to_path_buf));
+    match relative {
+        Some(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Some(relative) => relative.display().to_string(),
+        None => match env::var_os("HOME").and_then(|home| current.strip_prefix(home).ok()) {
+            Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+            Some(rest) => format!("~/{}", rest.display()),
+            None => current.display().to_string(),
+        },
+    }
+}
+
+/// Fish-style shortenings of `path`, longest first: each step cuts one more
+/// leading directory to its first letter, keeping the last one whole, e.g.
+/// `s/data/ui` then `s/d/ui`.
+pub fn abbreviations(path: &str) -> Vec<String> {
+    let parts: Vec<&str> = path.split('/').collect();
+    let mut out = Vec::new();
+    for cut in 1..parts.len() {
+        if first_letter(parts[cut - 1]) == parts[cut - 1] {
+            continue;
+        }
+        let text = parts
+            .iter()
+            .enumerate()
+            .map(|(i, part)| {
+                if i < cut {
+                    first_letter(part)
+                } else {
+                    part.to_string()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        out.push(text);
+    }
+    out
+}
+
+/// The first letter, keeping a leading dot so `.config` reads `.c`.
+fn first_letter(part: &str) -> String {
+    let dot = usize::from(part.starts_with('.'));
+    part.chars().take(dot + 1).collect()
+}
+
+/// Where Claude is working: `workspace.current_dir`, then `cwd`, then this
+/// process's own directory.
+pub fn current_dir(input: &StatusInput) -> Option<PathBuf> {
+    input
+        .workspace
+        .current_dir
+        .as_deref()
+        .or(input.cwd.as_deref())
+        .map(PathBuf::from)
+        .or_else(|| env::current_dir().ok())
+}
+
+/// `workspace.project_dir` when it contains `current`, else the nearest
+/// directory holding `.git`.
+fn project_root(input: &StatusInput, current: &Path) -> Option<PathBuf> {
+    let project = input
+        .workspace
+        .project_dir
+        .as_deref()
+        .map(PathBuf::from)
+        .filter(|project| current.starts_with(project));
+    project.or_else(|| {
+        current
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::abbreviations;
+
+    #[test]
+    fn shortens_leading_directories_first() {
+        assert_eq!(
+            abbreviations("services/api/src/data"),
+            ["s/api/src/data", "s/a/src/data", "s/a/s/data"]
+        );
+        assert_eq!(abbreviations("~/.config/nvim"), ["~/.c/nvim"]);
+        assert!(abbreviations("src").is_empty());
+    }
+}
@@ -1,5 +1,8 @@
 mod app;
+mod config;
 mod data;
+mod segment;
+mod stats;
 mod ui;
 
 use anyhow::Result;
@@ -9,50 +12,135 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{env, io, time::Duration};
+use std::{
+    env,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::config::{CliOverrides, Config};
+use crate::segment::Registry;
 
-struct CliConfig {
+struct CliArgs {
     preview: bool,
-    width: Option<usize>,
-    reserved: Option<usize>,
-    fill: Option<bool>,
+    /// Set by the `stats` subcommand, which takes the arguments that are
+    /// not global flags.
+    stats: Option<stats::StatsArgs>,
+    /// Read git and the directory from here instead of the payload's
+    /// `workspace.current_dir`.
+    cwd: Option<PathBuf>,
+    refresh_contributions: bool,
+    /// Set when re-run to finish a slow git read; see `data::REFRESH_GIT_FLAG`.
+    refresh_git: Option<String>,
+    overrides: CliOverrides,
 }
 
 fn main() -> Result<()> {
-    let config = parse_args();
-    if let Some(width) = config.width {
-        env::set_var("CC_STATUSLINE_WIDTH", width.to_string());
+    let args = parse_args(env::args().skip(1))?;
+    let statusline = args.stats.is_none()
+        && !args.preview
+        && !args.refresh_contributions
+        && args.refresh_git.is_none();
+    // The project file is looked up from Claude's workspace, which the
+    // statusline only learns from its payload.
+    let input = statusline.then(|| with_cwd(data::read_stdin(), args.cwd.as_deref()));
+    let workspace = match &input {
+        Some(input) => data::workspace::current_dir(input),
+        None => args.cwd.clone(),
+    };
+    let config = config::load(&args.overrides, workspace.as_deref());
+
+    if let Some(stats) = &args.stats {
+        return stats::run(stats, &config);
     }
-    if let Some(reserved) = config.reserved {
-        env::set_var("CC_STATUSLINE_RESERVED", reserved.to_string());
+
+    if args.refresh_contributions {
+        data::github::refresh(&config.github);
+        return Ok(());
     }
 
-    if let Some(fill) = config.fill {
-        env::set_var("CC_STATUSLINE_FILL", if fill { "1" } else { "0" });
+    if let Some(query) = &args.refresh_git {
+        data::refresh_git(&config, query);
+        return Ok(());
     }
 
-    if config.preview {
-        let mut terminal = setup_terminal()?;
-        let result = run_preview(&mut terminal);
-        restore_terminal(&mut terminal)?;
-        result
-    } else {
-        run_statusline()
+    let registry = Registry::builtin();
+    // A bad theme or layout falls back to the built-in ones rather than
+    // leaving the line empty.
+    let appearance = match ui::Appearance::from_config(&config, &registry) {
+        Ok(appearance) => appearance,
+        Err(error) => {
+            eprintln!("cc-sakura-line: {:#}", error);
+            let builtin = Config {
+                theme: None,
+                themes: Default::default(),
+                layout: Default::default(),
+                ..config.clone()
+            };
+            ui::Appearance::from_config(&builtin, &registry)?
+        }
+    };
+
+    match &input {
+        Some(input) => run_statusline(input, &config, &appearance, &registry),
+        None => {
+            let mut terminal = setup_terminal()?;
+            let app = app::App::new_preview(config, appearance, registry);
+            let result = run_preview(&mut terminal, app);
+            restore_terminal(&mut terminal)?;
+            result
+        }
     }
 }
 
-fn parse_args() -> CliConfig {
+/// Global flags go anywhere, before or after the `stats` subcommand.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<CliArgs> {
+    let mut stats = false;
+    let mut rest = Vec::new();
     let mut preview = false;
+    let mut refresh_contributions = false;
+    let mut refresh_git = None;
+    let mut config = None;
+    let mut cwd = None;
     let mut width = None;
     let mut reserved = None;
     let mut fill = None;
+    let mut color = None;
+    let mut glyphs = None;
+    let mut theme = None;
+
+    for arg in args {
+        if arg == "stats" {
+            stats = true;
+            continue;
+        }
 
-    for arg in env::args().skip(1) {
         if arg == "--preview" || arg == "-p" {
             preview = true;
             continue;
         }
 
+        if arg == data::github::REFRESH_FLAG {
+            refresh_contributions = true;
+            continue;
+        }
+
+        if let Some(value) = arg.strip_prefix(data::REFRESH_GIT_FLAG) {
+            refresh_git = Some(value.to_string());
+            continue;
+        }
+
+        if let Some(value) = arg.strip_prefix("--config=") {
+            config = Some(PathBuf::from(value));
+            continue;
+        }
+
+        if let Some(value) = arg.strip_prefix("--cwd=") {
+            cwd = Some(PathBuf::from(value));
+            continue;
+        }
+
         if let Some(value) = arg.strip_prefix("--width=") {
             if let Ok(parsed) = value.parse::<usize>() {
                 width = Some(parsed);
@@ -67,6 +155,21 @@ fn parse_args() -> CliConfig {
             continue;
         }
 
+        if let Some(value) = arg.strip_prefix("--color=") {
+            color = Some(value.to_string());
+            continue;
+        }
+
+        if let Some(value) = arg.strip_prefix("--glyphs=") {
+            glyphs = Some(value.to_string());
+            continue;
+        }
+
+        if let Some(value) = arg.strip_prefix("--theme=") {
+            theme = Some(value.to_string());
+            continue;
+        }
+
         if arg == "--fill" {
             fill = Some(true);
             continue;
@@ -76,22 +179,43 @@ fn parse_args() -> CliConfig {
             fill = Some(false);
             continue;
         }
+
+        rest.push(arg);
     }
 
-    CliConfig {
+    // Outside `stats`, unknown arguments are ignored as they always were.
+    let stats = if stats {
+        Some(stats::StatsArgs::parse(rest)?)
+    } else {
+        None
+    };
+
+    Ok(CliArgs {
         preview,
-        width,
-        reserved,
-        fill,
-    }
+        stats,
+        cwd,
+        refresh_contributions,
+        refresh_git,
+        overrides: CliOverrides {
+            config,
+            width,
+            reserved,
+            fill,
+            color,
+            glyphs,
+            theme,
+        },
+    })
 }
 
-fn run_preview(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<()> {
-    let mut app = app::App::new_preview();
+fn run_preview(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    mut app: app::App,
+) -> Result<()> {
     let tick_rate = Duration::from_millis(1000);
 
     loop {
-        terminal.draw(|frame| ui::render(frame, &app.snapshot))?;
+        terminal.draw(|frame| ui::render(frame, &app.snapshot, &app.config, &app.appearance))?;
 
         let timeout = tick_rate.saturating_sub(app.last_tick_elapsed());
         if event::poll(timeout)? {
@@ -108,11 +232,36 @@ fn run_preview(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Result<
     }
 }
 
-fn run_statusline() -> Result<()> {
-    let input = data::read_stdin_json();
-    let snapshot = data::collect_from_input(input.as_ref());
-    let output = ui::format_output(&snapshot);
+/// The payload with `--cwd` in place of its workspace directory.
+fn with_cwd(mut input: data::input::StatusInput, cwd: Option<&Path>) -> data::input::StatusInput {
+    if let Some(cwd) = cwd {
+        input.workspace.current_dir = Some(cwd.display().to_string());
+    }
+    input
+}
+
+fn run_statusline(
+    input: &data::input::StatusInput,
+    config: &Config,
+    appearance: &ui::Appearance,
+    registry: &Registry,
+) -> Result<()> {
+    let sources = data::Sources::from_input(input, config);
+    let snapshot = registry.collect(appearance.layout.segment_ids(), &sources);
+    let output = ui::format_output(&snapshot, config, appearance);
     print!("{}", output);
+    io::stdout().flush()?;
+    sources.finish();
+
+    // Book this session's cost even when no spend segment is shown; the
+    // line is out, so waiting on the ledger lock no longer holds it up.
+    sources.spend();
+
+    if config.history.enabled {
+        if let Some(record) = data::history::Record::from_sources(&sources) {
+            data::history::append(&record);
+        }
+    }
     Ok(())
 }
 
@@ -134,3 +283,56 @@ fn restore_terminal(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> Re
     terminal.show_cursor()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, with_cwd};
+    use crate::config::GitBackendKind;
+    use crate::data::{
+        git::{
+            self,
+            fixture::{repo, Scratch},
+        },
+        input::StatusInput,
+        workspace,
+    };
+    use crate::stats::GroupBy;
+
+    #[test]
+    fn stats_takes_global_flags_on_either_side() {
+        let args = |list: &[&str]| parse_args(list.iter().map(|arg| arg.to_string()));
+
+        let before = args(&["--config=x.toml", "stats", "--by=repo"]).unwrap();
+        assert_eq!(before.stats.unwrap().by, GroupBy::Repo);
+        assert_eq!(before.overrides.config.unwrap().to_str(), Some("x.toml"));
+
+        let after = args(&["stats", "--json", "--config=x.toml", "--cwd=/tmp"]).unwrap();
+        assert!(after.stats.unwrap().json);
+        assert_eq!(after.overrides.config.unwrap().to_str(), Some("x.toml"));
+        assert!(after.cwd.is_some());
+
+        assert!(args(&["stats", "--verbose"]).is_err());
+        assert!(args(&["--config=x.toml"]).unwrap().stats.is_none());
+    }
+
+    #[test]
+    fn cwd_overrides_the_payload_workspace() {
+        let scratch = Scratch::new("cwd");
+        repo(&scratch.0);
+
+        let args = parse_args([format!("--cwd={}", scratch.0.display())]).unwrap();
+        let mut input = StatusInput::default();
+        input.workspace.current_dir = Some("/nonexistent/elsewhere".to_string());
+        let input = with_cwd(input, args.cwd.as_deref());
+
+        let dir = workspace::current_dir(&input).unwrap();
+        assert_eq!(dir, scratch.0);
+        let query = git::Query {
+            dir,
+            session_start: None,
+            tag_pattern: None,
+        };
+        let name = scratch.0.file_name().unwrap().to_string_lossy();
+        assert_eq!(git::snapshot(GitBackendKind::Cli, &query).repository, name);
+    }
+}
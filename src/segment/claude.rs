@@ -0,0 +1,51 @@
+use crate::config::{Accent, CellStyle};
+use crate::data::{claude, session, Sources};
+use crate::segment::Segment;
+
+pub struct Model;
+
+impl Segment for Model {
+    fn id(&self) -> &'static str {
+        "model"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Pill, Accent::Primary)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        claude::model(sources.input, &sources.config.segments.model)
+    }
+}
+
+pub struct Version;
+
+impl Segment for Version {
+    fn id(&self) -> &'static str {
+        "version"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        claude::version(sources.input, &sources.config.segments.version)
+            .unwrap_or_else(|| "-".to_string())
+    }
+}
+
+pub struct Session;
+
+impl Segment for Session {
+    fn id(&self) -> &'static str {
+        "session"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Pill, Accent::Secondary)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        match sources.started_at {
+            Some(started_at) => session::clock(started_at),
+            None => session::from_input(sources.input),
+        }
+    }
+}
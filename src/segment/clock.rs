@@ -0,0 +1,19 @@
+use crate::config::{Accent, CellStyle};
+use crate::data::{clock, Sources};
+use crate::segment::Segment;
+
+pub struct Clock;
+
+impl Segment for Clock {
+    fn id(&self) -> &'static str {
+        "clock"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Pill, Accent::Secondary)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        clock::now(&sources.config.segments.clock)
+    }
+}
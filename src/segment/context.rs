@@ -0,0 +1,104 @@
+use crate::config::{Accent, CellStyle};
+use crate::data::Sources;
+use crate::segment::{Segment, Tone};
+
+pub struct Used;
+
+impl Segment for Used {
+    fn id(&self) -> &'static str {
+        "context"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Pill, Accent::Primary)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        sources.context().context.clone()
+    }
+}
+
+pub struct Remaining;
+
+impl Segment for Remaining {
+    fn id(&self) -> &'static str {
+        "context_remaining"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        sources.context().remaining.clone()
+    }
+}
+
+/// Current usage split into input, output, cache-write and cache-read tokens.
+pub struct Breakdown;
+
+impl Segment for Breakdown {
+    fn id(&self) -> &'static str {
+        "context_breakdown"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        sources.context().breakdown.clone()
+    }
+}
+
+/// Context usage as a bar, colored by `[segments.context_gauge]` thresholds.
+pub struct Gauge;
+
+impl Segment for Gauge {
+    fn id(&self) -> &'static str {
+        "context_gauge"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Gauge, Accent::Primary)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let Some(percent) = sources.context().used_percent else {
+            return "-".to_string();
+        };
+        let rounded = percent.round() as u64;
+        if percent >= sources.config.segments.context_gauge.compact {
+            format!("{}% !", rounded)
+        } else {
+            format!("{}%", rounded)
+        }
+    }
+
+    fn tone(&self, sources: &Sources<'_>) -> Tone {
+        let thresholds = &sources.config.segments.context_gauge;
+        match sources.context().used_percent {
+            None => Tone::Normal,
+            Some(p) if p >= thresholds.danger => Tone::Danger,
+            Some(p) if p >= thresholds.warn => Tone::Warn,
+            Some(_) => Tone::Calm,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Gauge;
+    use crate::config::Config;
+    use crate::data::{input::StatusInput, Sources};
+    use crate::segment::{Segment, Tone};
+
+    fn gauge_at(used: u64) -> (String, Tone) {
+        let mut config = Config::default();
+        config.segments.context.used = Some(used);
+        config.segments.context.total = Some(100);
+        let input = StatusInput::default();
+        let sources = Sources::from_input(&input, &config);
+        (Gauge.collect(&sources), Gauge.tone(&sources))
+    }
+
+    #[test]
+    fn gauge_tone_follows_thresholds() {
+        assert_eq!(gauge_at(30), ("30%".to_string(), Tone::Calm));
+        assert_eq!(gauge_at(65), ("65%".to_string(), Tone::Warn));
+        assert_eq!(gauge_at(85), ("85%".to_string(), Tone::Danger));
+        assert_eq!(gauge_at(93), ("93% !".to_string(), Tone::Danger));
+    }
+}
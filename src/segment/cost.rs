@@ -0,0 +1,110 @@
+use crate::config::{Accent, CellStyle};
+use crate::data::{
+    cost::{self, BudgetState},
+    Sources,
+};
+use crate::segment::{Segment, Tone};
+
+pub struct Cost;
+
+impl Segment for Cost {
+    fn id(&self) -> &'static str {
+        "cost"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Pill, Accent::Primary)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        cost::total(&sources.input.cost, &sources.config.segments.cost)
+    }
+}
+
+pub struct ApiTime;
+
+impl Segment for ApiTime {
+    fn id(&self) -> &'static str {
+        "api_time"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        cost::api_ratio(&sources.input.cost)
+    }
+}
+
+/// Claude-reported line changes; unlike `git_changes`, counts only what this
+/// session touched.
+pub struct LinesChanged;
+
+impl Segment for LinesChanged {
+    fn id(&self) -> &'static str {
+        "claude_changes"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Diff, Accent::Primary)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        cost::lines_changed(&sources.input.cost)
+    }
+}
+
+/// Today's spend across every session, against `budget.daily`.
+pub struct SpendToday;
+
+impl Segment for SpendToday {
+    fn id(&self) -> &'static str {
+        "spend_today"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let config = sources.config;
+        cost::spend(
+            "today",
+            sources.spend().today,
+            config.budget.daily,
+            &config.segments.cost,
+        )
+    }
+
+    fn tone(&self, sources: &Sources<'_>) -> Tone {
+        budget_tone(sources.spend().today, sources.config.budget.daily, sources)
+    }
+}
+
+/// Month-to-date spend across every session, against `budget.monthly`.
+pub struct SpendMonth;
+
+impl Segment for SpendMonth {
+    fn id(&self) -> &'static str {
+        "spend_month"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let config = sources.config;
+        cost::spend(
+            "month",
+            sources.spend().month,
+            config.budget.monthly,
+            &config.segments.cost,
+        )
+    }
+
+    fn tone(&self, sources: &Sources<'_>) -> Tone {
+        budget_tone(
+            sources.spend().month,
+            sources.config.budget.monthly,
+            sources,
+        )
+    }
+}
+
+fn budget_tone(usd: f64, limit: Option<f64>, sources: &Sources<'_>) -> Tone {
+    match cost::budget_state(usd, limit, &sources.config.budget) {
+        BudgetState::Under => Tone::Normal,
+        BudgetState::Approaching => Tone::Warn,
+        BudgetState::Reached => Tone::Danger,
+    }
+}
@@ -0,0 +1,244 @@
+use crate::config::{Accent, CellStyle};
+use crate::data::{git::GitInfo, session, Source, Sources, PENDING};
+use crate::segment::{Segment, Tone};
+
+pub struct Repository;
+
+impl Segment for Repository {
+    fn id(&self) -> &'static str {
+        "repository"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Pill, Accent::Primary)
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let host = sources
+            .git()
+            .filter(|_| sources.config.segments.repository.host)
+            .and_then(|git| git.remote.as_ref()?.host.as_deref());
+        let repository = git_field(sources, |git| &git.repository);
+        match host {
+            Some(host) => format!("{}/{}", host, repository),
+            None => repository,
+        }
+    }
+
+    /// Without the host, when it is shown.
+    fn shorter(&self, sources: &Sources<'_>) -> Vec<String> {
+        let repository = git_field(sources, |git| &git.repository);
+        if self.collect(sources) == repository {
+            Vec::new()
+        } else {
+            vec![repository]
+        }
+    }
+}
+
+pub struct Branch;
+
+impl Segment for Branch {
+    fn id(&self) -> &'static str {
+        "branch"
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        git_field(sources, |git| &git.branch)
+    }
+}
+
+pub struct Changes;
+
+impl Segment for Changes {
+    fn id(&self) -> &'static str {
+        "git_changes"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Diff, Accent::Primary)
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        git_field(sources, |git| &git.changes)
+    }
+}
+
+/// Files by kind of change: staged, modified, untracked, deleted, renamed
+/// and conflicted.
+pub struct Status;
+
+impl Segment for Status {
+    fn id(&self) -> &'static str {
+        "git_status"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Diff, Accent::Primary)
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        git_field(sources, |git| &git.status)
+    }
+}
+
+pub struct Stash;
+
+impl Segment for Stash {
+    fn id(&self) -> &'static str {
+        "stash"
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        git_field(sources, |git| &git.stash)
+    }
+}
+
+pub struct AheadBehind;
+
+impl Segment for AheadBehind {
+    fn id(&self) -> &'static str {
+        "ahead_behind"
+    }
+
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Pill, Accent::Secondary)
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        git_field(sources, |git| &git.ahead_behind)
+    }
+
+    /// Diverged or gone needs sorting out before the next push.
+    fn tone(&self, sources: &Sources<'_>) -> Tone {
+        match sources.git().map(|git| git.ahead_behind.as_str()) {
+            Some(value) if value == "gone" || value.starts_with("diverged") => Tone::Warn,
+            _ => Tone::Normal,
+        }
+    }
+}
+
+/// HEAD's short hash, age and subject: `1a2b3c4 4m ago Fix parser`. A
+/// narrow cell cuts the subject short.
+pub struct LastCommit;
+
+impl Segment for LastCommit {
+    fn id(&self) -> &'static str {
+        "last_commit"
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let Some(git) = sources.git() else {
+            return PENDING.to_string();
+        };
+        match &git.last_commit {
+            Some(commit) => format!(
+                "{} {} {}",
+                commit.short_id,
+                session::format_age(commit.time),
+                commit.subject
+            ),
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// Commits made since the Claude session started: `3 commits`.
+pub struct SessionCommits;
+
+impl Segment for SessionCommits {
+    fn id(&self) -> &'static str {
+        "session_commits"
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let Some(git) = sources.git() else {
+            return PENDING.to_string();
+        };
+        match git.session_commits {
+            Some(1) => "1 commit".to_string(),
+            Some(count) => format!("{} commits", count),
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// The nearest tag: `v1.2.3` when HEAD is on it, `v1.2.3 +4` commits past.
+pub struct NearestTag;
+
+impl Segment for NearestTag {
+    fn id(&self) -> &'static str {
+        "tag"
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let Some(git) = sources.git() else {
+            return PENDING.to_string();
+        };
+        match &git.tag {
+            Some(tag) if tag.since == 0 => tag.name.clone(),
+            Some(tag) => format!("{} +{}", tag.name, tag.since),
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// The branch being tracked, e.g. `origin/main`.
+pub struct Upstream;
+
+impl Segment for Upstream {
+    fn id(&self) -> &'static str {
+        "upstream"
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Git)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        git_field(sources, |git| &git.upstream)
+    }
+}
+
+fn git_field(sources: &Sources<'_>, field: impl Fn(&GitInfo) -> &String) -> String {
+    sources
+        .git()
+        .map(field)
+        .map_or_else(|| PENDING.to_string(), String::clone)
+}
@@ -0,0 +1,18 @@
+use crate::data::{Source, Sources, PENDING};
+use crate::segment::Segment;
+
+pub struct Contributions;
+
+impl Segment for Contributions {
+    fn id(&self) -> &'static str {
+        "contributions"
+    }
+
+    fn source(&self) -> Option<Source> {
+        Some(Source::Github)
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        sources.contributions().unwrap_or(PENDING).to_string()
+    }
+}
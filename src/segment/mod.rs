@@ -0,0 +1,226 @@
+mod claude;
+mod clock;
+mod context;
+mod cost;
+mod git;
+mod github;
+mod transcript;
+mod workspace;
+
+use std::collections::HashMap;
+
+use crate::config::{Accent, CellStyle};
+use crate::data::{Source, Sources, PENDING, STALE_MARKER};
+
+/// One statusline cell's worth of data. To add a segment, implement this in
+/// a new file and list it in `Registry::builtin`.
+pub trait Segment {
+    /// The id used in `[layout] rows`.
+    fn id(&self) -> &'static str;
+
+    /// How the cell looks unless the layout overrides it.
+    fn style(&self) -> (CellStyle, Accent) {
+        (CellStyle::Block, Accent::Primary)
+    }
+
+    /// The background source this segment reads, started before any
+    /// segment is collected so slow sources overlap.
+    fn source(&self) -> Option<Source> {
+        None
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String;
+
+    /// Shorter forms of the value, longest first. A cell too narrow for the
+    /// full value shows the longest of these that fits.
+    fn shorter(&self, _sources: &Sources<'_>) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// How urgent the collected value is; tints the cell whatever its style.
+    fn tone(&self, _sources: &Sources<'_>) -> Tone {
+        Tone::Normal
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tone {
+    #[default]
+    Normal,
+    Calm,
+    Warn,
+    Danger,
+}
+
+pub struct Registry {
+    segments: Vec<Box<dyn Segment>>,
+}
+
+impl Registry {
+    pub fn builtin() -> Self {
+        Self {
+            segments: vec![
+                // Claude
+                Box::new(claude::Model),
+                Box::new(claude::Version),
+                Box::new(github::Contributions),
+                Box::new(claude::Session),
+                Box::new(cost::Cost),
+                Box::new(cost::ApiTime),
+                Box::new(cost::LinesChanged),
+                Box::new(cost::SpendToday),
+                Box::new(cost::SpendMonth),
+                Box::new(transcript::Turn),
+                Box::new(transcript::Idle),
+                // Git
+                Box::new(git::Repository),
+                Box::new(workspace::Directory),
+                Box::new(git::Branch),
+                Box::new(git::NearestTag),
+                Box::new(git::Changes),
+                Box::new(git::Status),
+                Box::new(git::Stash),
+                Box::new(git::AheadBehind),
+                Box::new(git::Upstream),
+                Box::new(git::LastCommit),
+                Box::new(git::SessionCommits),
+                // Context
+                Box::new(context::Used),
+                Box::new(context::Remaining),
+                Box::new(context::Gauge),
+                Box::new(context::Breakdown),
+                Box::new(clock::Clock),
+                Box::new(Empty),
+            ],
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&dyn Segment> {
+        self.segments
+            .iter()
+            .find(|segment| segment.id() == id)
+            .map(|segment| segment.as_ref())
+    }
+
+    /// Collect the given segments; ids not in the registry are skipped.
+    pub fn collect<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+        sources: &Sources<'_>,
+    ) -> Snapshot {
+        let segments: Vec<_> = ids.into_iter().filter_map(|id| self.get(id)).collect();
+        for source in segments.iter().filter_map(|segment| segment.source()) {
+            sources.start(source);
+        }
+
+        let mut snapshot = Snapshot::default();
+        for segment in segments {
+            if snapshot.values.contains_key(segment.id()) {
+                continue;
+            }
+            let id = segment.id().to_string();
+            let tone = segment.tone(sources);
+            let mut shorter = segment.shorter(sources);
+            let mut value = segment.collect(sources);
+            if segment
+                .source()
+                .is_some_and(|source| sources.is_stale(source))
+            {
+                for value in std::iter::once(&mut value).chain(&mut shorter) {
+                    mark_stale(value);
+                }
+            }
+            snapshot.tones.insert(id.clone(), tone);
+            snapshot.shorter.insert(id.clone(), shorter);
+            snapshot.values.insert(id, value);
+        }
+        snapshot
+    }
+}
+
+/// Mark a value read from a last known source, unless it says nothing or
+/// is marked already.
+fn mark_stale(value: &mut String) {
+    let blank = value.is_empty() || value == "-" || value == PENDING;
+    if !blank && !value.ends_with(STALE_MARKER) {
+        value.push_str(STALE_MARKER);
+    }
+}
+
+/// Collected segment values, keyed by segment id.
+#[derive(Clone, Debug, Default)]
+pub struct Snapshot {
+    values: HashMap<String, String>,
+    tones: HashMap<String, Tone>,
+    shorter: HashMap<String, Vec<String>>,
+}
+
+impl Snapshot {
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.values.get(id).map(String::as_str)
+    }
+
+    pub fn tone(&self, id: &str) -> Tone {
+        self.tones.get(id).copied().unwrap_or_default()
+    }
+
+    pub fn shorter(&self, id: &str) -> &[String] {
+        self.shorter.get(id).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Snapshot {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            values: iter
+                .into_iter()
+                .map(|(id, value)| (id.into(), value.into()))
+                .collect(),
+            tones: HashMap::new(),
+            shorter: HashMap::new(),
+        }
+    }
+}
+
+/// A blank cell, for keeping later cells in their column.
+struct Empty;
+
+impl Segment for Empty {
+    fn id(&self) -> &'static str {
+        "empty"
+    }
+
+    fn collect(&self, _sources: &Sources<'_>) -> String {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+    use crate::config::Config;
+    use crate::data::{input::StatusInput, Sources};
+    use std::collections::HashSet;
+
+    #[test]
+    fn builtin_ids_are_unique() {
+        let registry = Registry::builtin();
+        let mut seen = HashSet::new();
+        for segment in &registry.segments {
+            assert!(seen.insert(segment.id()), "duplicate id {}", segment.id());
+        }
+    }
+
+    #[test]
+    fn collect_skips_unknown_ids() {
+        let mut config = Config::default();
+        config.segments.model.name = Some("Opus".to_string());
+        let input = StatusInput::default();
+        let sources = Sources::from_input(&input, &config);
+
+        let snapshot = Registry::builtin().collect(["model", "nope", "empty"], &sources);
+        assert_eq!(snapshot.get("model"), Some("Opus"));
+        assert_eq!(snapshot.get("empty"), Some(""));
+        assert_eq!(snapshot.get("nope"), None);
+    }
+}
@@ -0,0 +1,40 @@
+use jiff::Timestamp;
+
+use crate::data::{session, Sources};
+use crate::segment::Segment;
+
+/// Prompts so far and the latest tool used, e.g. `turn 14 · Edit`.
+pub struct Turn;
+
+impl Segment for Turn {
+    fn id(&self) -> &'static str {
+        "turn"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        match sources.transcript() {
+            Some(info) => match &info.last_tool {
+                Some(tool) => format!("turn {} · {}", info.turns, tool),
+                None => format!("turn {}", info.turns),
+            },
+            None => "-".to_string(),
+        }
+    }
+}
+
+/// Time since Claude last replied, e.g. `idle 3m`.
+pub struct Idle;
+
+impl Segment for Idle {
+    fn id(&self) -> &'static str {
+        "idle"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        let Some(at) = sources.transcript().and_then(|info| info.last_reply_at) else {
+            return "-".to_string();
+        };
+        let secs = Timestamp::now().duration_since(at).as_secs().max(0) as u64;
+        format!("idle {}", session::format_duration(secs))
+    }
+}
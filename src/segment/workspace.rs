@@ -0,0 +1,19 @@
+use crate::data::{workspace, Sources};
+use crate::segment::Segment;
+
+/// Working directory within the project, shortened fish-style to fit.
+pub struct Directory;
+
+impl Segment for Directory {
+    fn id(&self) -> &'static str {
+        "directory"
+    }
+
+    fn collect(&self, sources: &Sources<'_>) -> String {
+        workspace::directory(sources.input)
+    }
+
+    fn shorter(&self, sources: &Sources<'_>) -> Vec<String> {
+        workspace::abbreviations(&self.collect(sources))
+    }
+}
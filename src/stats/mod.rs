@@ -0,0 +1,276 @@
+use anyhow::{bail, Result};
+use jiff::{civil::Date, tz::TimeZone, ToSpan};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::config::Config;
+use crate::data::{clock, context::format_tokens, cost, history, session};
+
+/// The most `--days` goes back; a century of history is all of it, and
+/// larger values overflow date arithmetic.
+const MAX_DAYS: i64 = 36_500;
+
+/// What `stats` rows are grouped by.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GroupBy {
+    #[default]
+    Day,
+    Repo,
+    Model,
+}
+
+#[derive(Debug, Default)]
+pub struct StatsArgs {
+    pub by: GroupBy,
+    /// Only the last this many days, today included.
+    pub days: Option<i64>,
+    pub json: bool,
+}
+
+impl StatsArgs {
+    /// Parse the arguments after `stats`.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self::default();
+        for arg in args {
+            if arg == "--json" {
+                parsed.json = true;
+            } else if let Some(value) = arg.strip_prefix("--by=") {
+                parsed.by = match value {
+                    "day" => GroupBy::Day,
+                    "repo" => GroupBy::Repo,
+                    "model" => GroupBy::Model,
+                    _ => bail!("unknown --by value `{}`: use day, repo or model", value),
+                };
+            } else if let Some(value) = arg.strip_prefix("--days=") {
+                match value.parse() {
+                    Ok(days) if (1..=MAX_DAYS).contains(&days) => parsed.days = Some(days),
+                    _ => bail!(
+                        "--days needs a number from 1 to {}, got `{}`",
+                        MAX_DAYS,
+                        value
+                    ),
+                }
+            } else {
+                bail!("unknown stats argument `{}`", arg);
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+/// Totals for one group. Sessions span groups when, say, they run past
+/// midnight; each group only gets what was spent while in it.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Row {
+    pub key: String,
+    pub sessions: usize,
+    pub duration_ms: u64,
+    pub tokens: u64,
+    pub cost_usd: f64,
+}
+
+pub fn run(args: &StatsArgs, config: &Config) -> Result<()> {
+    let since = match args.days {
+        Some(days) => Some(clock::today().checked_sub((days - 1).days())?),
+        None => None,
+    };
+    let rows = summarize(&history::read_all(), args.by, since, &TimeZone::system());
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+    } else {
+        print!("{}", table(&rows, args.by, config));
+    }
+    Ok(())
+}
+
+/// Group records, counting the growth of each session's running totals
+/// since its previous record, so repeated renders are not double counted.
+fn summarize(
+    records: &[history::Record],
+    by: GroupBy,
+    since: Option<Date>,
+    zone: &TimeZone,
+) -> Vec<Row> {
+    #[derive(Default)]
+    struct Totals {
+        tokens: u64,
+        cost_usd: f64,
+        duration_ms: u64,
+    }
+
+    let mut previous: HashMap<&str, Totals> = HashMap::new();
+    let mut groups: BTreeMap<String, (Row, BTreeSet<&str>)> = BTreeMap::new();
+
+    for record in records {
+        let last = previous.entry(record.session.as_str()).or_default();
+        let tokens = record.tokens.unwrap_or(last.tokens);
+        let cost_usd = record.cost_usd.unwrap_or(last.cost_usd);
+        let duration_ms = record.duration_ms.unwrap_or(last.duration_ms);
+        let growth = Totals {
+            tokens: tokens.saturating_sub(last.tokens),
+            cost_usd: (cost_usd - last.cost_usd).max(0.0),
+            duration_ms: duration_ms.saturating_sub(last.duration_ms),
+        };
+        *last = Totals {
+            tokens,
+            cost_usd,
+            duration_ms,
+        };
+
+        let Some(at) = record.at else {
+            continue;
+        };
+        let day = at.to_zoned(zone.clone()).date();
+        if since.is_some_and(|since| day < since) {
+            continue;
+        }
+
+        let key = match by {
+            GroupBy::Day => day.to_string(),
+            GroupBy::Repo => record.repo.clone().unwrap_or_else(|| "-".to_string()),
+            GroupBy::Model => record.model.clone().unwrap_or_else(|| "-".to_string()),
+        };
+        let (row, sessions) = groups.entry(key.clone()).or_insert_with(|| {
+            let row = Row {
+                key,
+                ..Row::default()
+            };
+            (row, BTreeSet::new())
+        });
+        row.tokens += growth.tokens;
+        row.cost_usd += growth.cost_usd;
+        row.duration_ms += growth.duration_ms;
+        sessions.insert(record.session.as_str());
+    }
+
+    let mut rows: Vec<Row> = groups
+        .into_values()
+        .map(|(row, sessions)| Row {
+            sessions: sessions.len(),
+            ..row
+        })
+        .collect();
+    // Days read best in order; repos and models by what they cost.
+    if by != GroupBy::Day {
+        rows.sort_by(|a, b| b.cost_usd.total_cmp(&a.cost_usd));
+    }
+    rows
+}
+
+fn table(rows: &[Row], by: GroupBy, config: &Config) -> String {
+    let heading = match by {
+        GroupBy::Day => "day",
+        GroupBy::Repo => "repo",
+        GroupBy::Model => "model",
+    };
+    if rows.is_empty() {
+        return "no history yet\n".to_string();
+    }
+
+    let cost_config = &config.segments.cost;
+    let cells: Vec<[String; 5]> = rows
+        .iter()
+        .map(|row| {
+            [
+                row.key.clone(),
+                row.sessions.to_string(),
+                session::format_duration(row.duration_ms / 1000),
+                format_tokens(row.tokens, 1),
+                cost::format_amount(row.cost_usd * cost_config.rate, cost_config),
+            ]
+        })
+        .collect();
+
+    let header = [heading, "sessions", "time", "tokens", "cost"].map(String::from);
+    let mut widths = header.clone().map(|cell| cell.len());
+    for line in &cells {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut out = String::new();
+    for line in std::iter::once(&header).chain(&cells) {
+        let mut text = format!("{:<1$}", line[0], widths[0]);
+        for (cell, width) in line.iter().zip(widths).skip(1) {
+            text.push_str(&format!("  {:>1$}", cell, width));
+        }
+        out.push_str(text.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{summarize, GroupBy, Row, StatsArgs};
+    use crate::data::history::Record;
+    use jiff::{civil::date, tz::TimeZone};
+
+    fn record(at: &str, session: &str, repo: &str, cost_usd: f64, tokens: u64) -> Record {
+        Record {
+            at: Some(at.parse().unwrap()),
+            session: session.to_string(),
+            repo: Some(repo.to_string()),
+            tokens: Some(tokens),
+            cost_usd: Some(cost_usd),
+            ..Record::default()
+        }
+    }
+
+    #[test]
+    fn counts_growth_once_per_group() {
+        let records = [
+            record("2024-03-10T23:50:00Z", "a", "sakura", 1.0, 1_000),
+            record("2024-03-10T23:55:00Z", "a", "sakura", 1.5, 1_500),
+            record("2024-03-11T00:10:00Z", "a", "sakura", 2.0, 2_000),
+            record("2024-03-11T09:00:00Z", "b", "other", 0.5, 300),
+        ];
+
+        let by_day = summarize(&records, GroupBy::Day, None, &TimeZone::UTC);
+        assert_eq!(
+            by_day,
+            vec![
+                Row {
+                    key: "2024-03-10".to_string(),
+                    sessions: 1,
+                    duration_ms: 0,
+                    tokens: 1_500,
+                    cost_usd: 1.5,
+                },
+                Row {
+                    key: "2024-03-11".to_string(),
+                    sessions: 2,
+                    duration_ms: 0,
+                    tokens: 800,
+                    cost_usd: 1.0,
+                },
+            ]
+        );
+
+        let by_repo = summarize(&records, GroupBy::Repo, None, &TimeZone::UTC);
+        assert_eq!(by_repo[0].key, "sakura");
+        assert_eq!(by_repo[0].cost_usd, 2.0);
+
+        let recent = summarize(
+            &records,
+            GroupBy::Day,
+            Some(date(2024, 3, 11)),
+            &TimeZone::UTC,
+        );
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].cost_usd, 1.0);
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        let args = |list: &[&str]| StatsArgs::parse(list.iter().map(|arg| arg.to_string()));
+        assert_eq!(args(&["--by=repo"]).unwrap().by, GroupBy::Repo);
+        assert!(args(&["--by=week"]).is_err());
+        assert!(args(&["--days=0"]).is_err());
+        assert_eq!(args(&["--days=36500"]).unwrap().days, Some(36_500));
+        assert!(args(&["--days=99999999"]).is_err());
+        assert!(args(&["--verbose"]).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use ratatui::style::Color;
+
+use crate::config::ColorMode;
+
+/// How many colors the statusline may use.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorDepth {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+    NoColor,
+}
+
+/// xterm's default RGB values for the 16 basic colors, in ANSI order.
+const BASIC: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// Resolve `mode`; `auto` follows `NO_COLOR`, `COLORTERM` and `TERM`.
+    pub fn detect(mode: ColorMode, env: impl Fn(&str) -> Option<String>) -> Self {
+        match mode {
+            ColorMode::Truecolor => return Self::TrueColor,
+            ColorMode::Ansi256 => return Self::Ansi256,
+            ColorMode::Ansi16 => return Self::Ansi16,
+            ColorMode::None => return Self::NoColor,
+            ColorMode::Auto => {}
+        }
+
+        if env("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+            return Self::NoColor;
+        }
+
+        if let Some(colorterm) = env("COLORTERM") {
+            if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+                return Self::TrueColor;
+            }
+        }
+
+        match env("TERM") {
+            // No TERM usually means we were spawned without a terminal of our
+            // own; keep the full palette, as before detection existed.
+            None => Self::TrueColor,
+            Some(term) if term == "dumb" => Self::NoColor,
+            Some(term) if term.contains("direct") => Self::TrueColor,
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            Some(_) => Self::Ansi16,
+        }
+    }
+
+    /// Map a color into what this depth can show.
+    pub fn adapt(self, color: Color) -> Color {
+        match self {
+            Self::TrueColor => color,
+            Self::NoColor => Color::Reset,
+            Self::Ansi256 => match color {
+                Color::Rgb(r, g, b) => Color::Indexed(nearest_256((r, g, b))),
+                other => other,
+            },
+            Self::Ansi16 => match to_rgb(color) {
+                Some(rgb) => nearest_basic(rgb),
+                None => color,
+            },
+        }
+    }
+}
+
+/// Foreground + background escape sequence; empty when colors are off.
+pub fn ansi_fg_bg(fg: Color, bg: Color, depth: ColorDepth) -> String {
+    if depth == ColorDepth::NoColor {
+        return String::new();
+    }
+    format!("{}{}", ansi_color(fg, false), ansi_color(bg, true))
+}
+
+fn ansi_color(color: Color, background: bool) -> String {
+    let base = if background { 48 } else { 38 };
+    match color {
+        Color::Rgb(r, g, b) => format!("\x1b[{};2;{};{};{}m", base, r, g, b),
+        Color::Indexed(idx) => format!("\x1b[{};5;{}m", base, idx),
+        Color::Reset => format!("\x1b[{}m", base + 1),
+        named => {
+            let idx = BASIC
+                .iter()
+                .position(|(basic, _)| *basic == named)
+                .unwrap_or(7) as u8;
+            let code = if idx < 8 { 30 + idx } else { 90 + idx - 8 };
+            let code = if background { code + 10 } else { code };
+            format!("\x1b[{}m", code)
+        }
+    }
+}
+
+fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(idx) => Some(indexed_rgb(idx)),
+        _ => None,
+    }
+}
+
+fn indexed_rgb(idx: u8) -> (u8, u8, u8) {
+    match idx {
+        0..=15 => BASIC[idx as usize].1,
+        16..=231 => {
+            let idx = idx - 16;
+            (
+                CUBE_LEVELS[(idx / 36) as usize],
+                CUBE_LEVELS[(idx / 6 % 6) as usize],
+                CUBE_LEVELS[(idx % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + (idx - 232) * 10;
+            (level, level, level)
+        }
+    }
+}
+
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let cube_index = |v: u8| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| (**level as i32 - v as i32).abs())
+            .map(|(i, _)| i as u8)
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (cube_index(rgb.0), cube_index(rgb.1), cube_index(rgb.2));
+    let cube = 16 + 36 * r + 6 * g + b;
+
+    let avg = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let gray_step = ((avg.saturating_sub(8) + 5) / 10).min(23) as u8;
+    let gray = 232 + gray_step;
+
+    if distance(rgb, indexed_rgb(gray)) < distance(rgb, indexed_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// Pick a basic color by hue rather than RGB distance, which would turn
+/// every pastel into gray.
+fn nearest_basic((r, g, b): (u8, u8, u8)) -> Color {
+    let max = r.max(g).max(b) as f32;
+    let min = r.min(g).min(b) as f32;
+    let value = max / 255.0;
+    let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+
+    if value < 0.2 {
+        return Color::Black;
+    }
+
+    if saturation < 0.25 {
+        return match value {
+            v if v < 0.4 => Color::Black,
+            v if v < 0.65 => Color::DarkGray,
+            v if v < 0.85 => Color::Gray,
+            _ => Color::White,
+        };
+    }
+
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let delta = max - min;
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+
+    let bright = value > 0.6;
+    let pick = |normal: Color, light: Color| if bright { light } else { normal };
+    match hue {
+        h if !(30.0..330.0).contains(&h) => pick(Color::Red, Color::LightRed),
+        h if h < 90.0 => pick(Color::Yellow, Color::LightYellow),
+        h if h < 150.0 => pick(Color::Green, Color::LightGreen),
+        h if h < 210.0 => pick(Color::Cyan, Color::LightCyan),
+        h if h < 270.0 => pick(Color::Blue, Color::LightBlue),
+        _ => pick(Color::Magenta, Color::LightMagenta),
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ansi_fg_bg, ColorDepth};
+    use crate::config::ColorMode;
+    use ratatui::style::Color;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorDepth {
+        ColorDepth::detect(ColorMode::Auto, |key| {
+            vars.iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+        })
+    }
+
+    #[test]
+    fn detects_from_env() {
+        assert_eq!(detect(&[]), ColorDepth::TrueColor);
+        assert_eq!(
+            detect(&[("COLORTERM", "truecolor"), ("TERM", "xterm")]),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(detect(&[("TERM", "screen-256color")]), ColorDepth::Ansi256);
+        assert_eq!(detect(&[("TERM", "xterm")]), ColorDepth::Ansi16);
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]),
+            ColorDepth::NoColor
+        );
+    }
+
+    #[test]
+    fn explicit_mode_wins_over_env() {
+        let depth = ColorDepth::detect(ColorMode::Ansi256, |_| Some("1".to_string()));
+        assert_eq!(depth, ColorDepth::Ansi256);
+    }
+
+    #[test]
+    fn quantizes_to_256() {
+        assert_eq!(
+            ColorDepth::Ansi256.adapt(Color::Rgb(255, 0, 0)),
+            Color::Indexed(196)
+        );
+        assert_eq!(
+            ColorDepth::Ansi256.adapt(Color::Rgb(128, 128, 128)),
+            Color::Indexed(244)
+        );
+    }
+
+    #[test]
+    fn quantizes_to_16() {
+        assert_eq!(
+            ColorDepth::Ansi16.adapt(Color::Rgb(10, 10, 10)),
+            Color::Black
+        );
+        assert_eq!(
+            ColorDepth::Ansi16.adapt(Color::Rgb(250, 250, 250)),
+            Color::White
+        );
+        assert_eq!(
+            ColorDepth::Ansi16.adapt(Color::Indexed(196)),
+            Color::LightRed
+        );
+        // Pastels keep their hue instead of fading to gray.
+        assert_eq!(
+            ColorDepth::Ansi16.adapt(Color::Rgb(241, 157, 181)),
+            Color::LightRed
+        );
+        assert_eq!(
+            ColorDepth::Ansi16.adapt(Color::Rgb(154, 199, 122)),
+            Color::LightGreen
+        );
+    }
+
+    #[test]
+    fn escapes_per_depth() {
+        assert_eq!(
+            ansi_fg_bg(Color::Indexed(196), Color::Black, ColorDepth::Ansi256),
+            "\x1b[38;5;196m\x1b[40m"
+        );
+        assert_eq!(
+            ansi_fg_bg(Color::LightRed, Color::Gray, ColorDepth::Ansi16),
+            "\x1b[91m\x1b[47m"
+        );
+        assert_eq!(ansi_fg_bg(Color::Red, Color::Blue, ColorDepth::NoColor), "");
+    }
+}
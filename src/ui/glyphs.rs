@@ -0,0 +1,77 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::GlyphStyle;
+
+/// The pill end caps. Powerline caps are drawn in the pill color on the row
+/// background, so they only make sense when colors are on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GlyphSet {
+    pub left: &'static str,
+    pub right: &'static str,
+    needs_color: bool,
+}
+
+impl GlyphSet {
+    pub fn from_style(style: GlyphStyle) -> Self {
+        let (left, right, needs_color) = match style {
+            GlyphStyle::Rounded => ("\u{e0b6}", "\u{e0b4}", true),
+            GlyphStyle::Angled => ("\u{e0b2}", "\u{e0b0}", true),
+            GlyphStyle::Slanted => ("\u{e0ba}", "\u{e0bc}", true),
+            GlyphStyle::Ascii => ("[", "]", false),
+            GlyphStyle::None => ("", "", false),
+        };
+        Self {
+            left,
+            right,
+            needs_color,
+        }
+    }
+
+    /// Columns both caps take together.
+    pub fn width(&self) -> usize {
+        self.left.width() + self.right.width()
+    }
+
+    /// The caps to draw; shape-only glyphs become blanks of the same width
+    /// when there is no color.
+    pub fn caps(&self, colored: bool) -> (&'static str, &'static str) {
+        if colored || !self.needs_color {
+            return (self.left, self.right);
+        }
+        (blank(self.left.width()), blank(self.right.width()))
+    }
+}
+
+impl Default for GlyphSet {
+    fn default() -> Self {
+        Self::from_style(GlyphStyle::default())
+    }
+}
+
+fn blank(width: usize) -> &'static str {
+    const SPACES: &str = "    ";
+    &SPACES[..width.min(SPACES.len())]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GlyphSet;
+    use crate::config::GlyphStyle;
+
+    #[test]
+    fn widths_follow_the_set() {
+        assert_eq!(GlyphSet::from_style(GlyphStyle::Rounded).width(), 2);
+        assert_eq!(GlyphSet::from_style(GlyphStyle::Ascii).width(), 2);
+        assert_eq!(GlyphSet::from_style(GlyphStyle::None).width(), 0);
+    }
+
+    #[test]
+    fn powerline_caps_blank_out_without_color() {
+        let rounded = GlyphSet::from_style(GlyphStyle::Rounded);
+        assert_eq!(rounded.caps(false), (" ", " "));
+        assert_eq!(rounded.caps(true), ("\u{e0b6}", "\u{e0b4}"));
+
+        let ascii = GlyphSet::from_style(GlyphStyle::Ascii);
+        assert_eq!(ascii.caps(false), ("[", "]"));
+    }
+}
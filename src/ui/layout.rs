@@ -0,0 +1,193 @@
+use anyhow::{bail, Result};
+
+use crate::config::{Accent, CellConfig, CellStyle, Config};
+use crate::segment::{Registry, Segment};
+
+const DEFAULT_ROWS: [[&str; 4]; 3] = [
+    // Row 1: Claude
+    ["model", "version", "contributions", "session"],
+    // Row 2: Git
+    ["repository", "branch", "git_changes", "ahead_behind"],
+    // Row 3: Context
+    ["context", "context_remaining", "empty", "clock"],
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayoutCell {
+    pub id: String,
+    pub style: CellStyle,
+    pub accent: Accent,
+}
+
+/// Rows of segments plus the column split used in fill mode. Cells share a
+/// width with the cells in the same position on other rows.
+#[derive(Clone, Debug)]
+pub struct StatusLayout {
+    pub rows: Vec<Vec<LayoutCell>>,
+    columns: Vec<u16>,
+}
+
+impl StatusLayout {
+    pub fn from_config(config: &Config, registry: &Registry) -> Result<Self> {
+        let rows = config.layout.rows.clone().unwrap_or_else(default_rows);
+        let rows = rows
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| LayoutCell::from_config(cell, registry))
+                    .collect()
+            })
+            .collect::<Result<Vec<Vec<_>>>>()?;
+
+        let count = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let columns = match &config.layout.columns {
+            Some(columns) => {
+                if columns.len() != count {
+                    bail!(
+                        "layout.columns has {} entries but the widest row has {} cells",
+                        columns.len(),
+                        count
+                    );
+                }
+                if columns.iter().map(|pct| *pct as u32).sum::<u32>() > 100 {
+                    bail!("layout.columns adds up to more than 100%");
+                }
+                columns.clone()
+            }
+            None => vec![100 / count.max(1) as u16; count],
+        };
+
+        Ok(Self { rows, columns })
+    }
+
+    /// Every segment id the layout shows, in reading order.
+    pub fn segment_ids(&self) -> impl Iterator<Item = &str> {
+        self.rows.iter().flatten().map(|cell| cell.id.as_str())
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Split `total_width` by the column percentages; the last column takes
+    /// whatever rounding leaves over.
+    pub fn column_widths(&self, total_width: usize) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .map(|pct| total_width * (*pct as usize) / 100)
+            .collect();
+
+        let used: usize = widths.iter().sum();
+        if let Some(last) = widths.last_mut() {
+            if used < total_width {
+                *last += total_width - used;
+            }
+        }
+
+        widths
+    }
+}
+
+impl Default for StatusLayout {
+    fn default() -> Self {
+        Self::from_config(&Config::default(), &Registry::builtin())
+            .expect("built-in layout is valid")
+    }
+}
+
+fn default_rows() -> Vec<Vec<CellConfig>> {
+    DEFAULT_ROWS
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|id| CellConfig::Id(id.to_string()))
+                .collect()
+        })
+        .collect()
+}
+
+impl LayoutCell {
+    fn new(segment: &dyn Segment) -> Self {
+        let (style, accent) = segment.style();
+        Self {
+            id: segment.id().to_string(),
+            style,
+            accent,
+        }
+    }
+
+    fn from_config(cell: &CellConfig, registry: &Registry) -> Result<Self> {
+        let (id, style, accent) = match cell {
+            CellConfig::Id(id) => (id, None, None),
+            CellConfig::Styled { id, style, accent } => (id, *style, *accent),
+        };
+
+        let Some(segment) = registry.get(id) else {
+            bail!("unknown segment `{}` in layout", id);
+        };
+
+        let mut cell = Self::new(segment);
+        if let Some(style) = style {
+            cell.style = style;
+        }
+        if let Some(accent) = accent {
+            cell.accent = accent;
+        }
+        Ok(cell)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatusLayout;
+    use crate::config::{Accent, CellStyle, Config};
+    use crate::segment::Registry;
+
+    fn layout_from(text: &str) -> anyhow::Result<StatusLayout> {
+        let config: Config = toml::from_str(text).unwrap();
+        StatusLayout::from_config(&config, &Registry::builtin())
+    }
+
+    #[test]
+    fn default_layout_is_three_by_four() {
+        let layout = StatusLayout::default();
+        assert_eq!(layout.rows.len(), 3);
+        assert_eq!(layout.column_count(), 4);
+        assert_eq!(layout.rows[0][0].style, CellStyle::Pill);
+        assert_eq!(layout.rows[2][3].accent, Accent::Secondary);
+    }
+
+    #[test]
+    fn custom_rows_with_styles() {
+        let layout = layout_from(
+            r#"
+            [layout]
+            rows = [
+              ["model", { id = "clock", style = "block" }],
+              [{ id = "branch", style = "pill", accent = "secondary" }],
+            ]
+            columns = [60, 40]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(layout.rows.len(), 2);
+        assert_eq!(layout.column_count(), 2);
+        assert_eq!(layout.rows[0][1].style, CellStyle::Block);
+        assert_eq!(layout.rows[1][0].style, CellStyle::Pill);
+        assert_eq!(layout.rows[1][0].accent, Accent::Secondary);
+        assert_eq!(layout.column_widths(100), vec![60, 40]);
+    }
+
+    #[test]
+    fn rejects_unknown_segments_and_bad_columns() {
+        assert!(layout_from("[layout]\nrows = [[\"nope\"]]").is_err());
+        assert!(layout_from("[layout]\nrows = [[\"model\", \"clock\"]]\ncolumns = [100]").is_err());
+    }
+
+    #[test]
+    fn last_column_takes_remainder() {
+        let layout = StatusLayout::default();
+        assert_eq!(layout.column_widths(42), vec![10, 10, 10, 12]);
+    }
+}
@@ -1,3 +1,9 @@
+mod color;
+mod glyphs;
+mod layout;
+mod theme;
+
+use anyhow::Result;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
@@ -11,40 +17,86 @@ use std::{
 };
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::data::Snapshot;
-
-const SAKURA: Color = Color::Rgb(241, 157, 181);
-const SAKURA_FG: Color = Color::Rgb(35, 30, 30);
-const GREEN: Color = Color::Rgb(154, 199, 122);
-const GREEN_FG: Color = Color::Rgb(30, 45, 28);
-const ROW_BG: Color = Color::Rgb(40, 40, 40);
-const ROW_FG: Color = Color::Rgb(220, 220, 220);
-const MID_BG: Color = Color::Rgb(55, 55, 55);
-const MID_FG: Color = Color::Rgb(220, 220, 220);
-const PLUS_FG: Color = Color::Rgb(98, 201, 98);
-const MINUS_FG: Color = Color::Rgb(235, 110, 110);
+use color::ansi_fg_bg;
+
+use crate::config::{Accent, CellStyle, Config};
+use crate::segment::{Registry, Snapshot, Tone};
+
+pub use color::ColorDepth;
+pub use glyphs::GlyphSet;
+pub use layout::{LayoutCell, StatusLayout};
+pub use theme::Theme;
+
 const LINE_PREFIX: &str = " ";
-const COL_PCTS: [u16; 4] = [25, 25, 25, 25];
-const ROUND_LEFT: &str = "";
-const ROUND_RIGHT: &str = "";
-const PILL_BORDER_WIDTH: usize = 2;
+/// Bar width a gauge asks for when columns size to content.
+const GAUGE_BAR: usize = 10;
+/// Narrower than this, a gauge shows only its label.
+const GAUGE_MIN_BAR: usize = 3;
+const GAUGE_EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+/// Everything resolved from the config that decides how a snapshot is drawn.
+#[derive(Clone, Debug, Default)]
+pub struct Appearance {
+    /// Already mapped into `depth`, so the preview and the ANSI output agree.
+    pub theme: Theme,
+    pub layout: StatusLayout,
+    pub depth: ColorDepth,
+    pub glyphs: GlyphSet,
+}
 
-type Segment<'a> = (usize, &'a str);
+impl Appearance {
+    pub fn from_config(config: &Config, registry: &Registry) -> Result<Self> {
+        let depth = ColorDepth::detect(config.color, |key| std::env::var(key).ok());
+        Ok(Self {
+            theme: Theme::from_config(config)?.adapted(depth),
+            layout: StatusLayout::from_config(config, registry)?,
+            depth,
+            glyphs: GlyphSet::from_style(config.glyphs),
+        })
+    }
+}
 
-pub fn render(frame: &mut Frame<'_>, snapshot: &Snapshot) {
+struct Cell<'a> {
+    column: usize,
+    value: &'a str,
+    tone: Tone,
+    shorter: &'a [String],
+    layout: &'a LayoutCell,
+}
+
+impl<'a> Cell<'a> {
+    /// This cell showing the longest of its values that fits `width`.
+    fn fitted(&self, width: usize, appearance: &Appearance) -> Cell<'a> {
+        let value = std::iter::once(self.value)
+            .chain(self.shorter.iter().map(String::as_str))
+            .find(|value| natural_width(value, self.layout.style, appearance) <= width)
+            .or(self.shorter.last().map(String::as_str))
+            .unwrap_or(self.value);
+        Cell { value, ..*self }
+    }
+}
+
+pub fn render(
+    frame: &mut Frame<'_>,
+    snapshot: &Snapshot,
+    config: &Config,
+    appearance: &Appearance,
+) {
     let area = frame.size();
     let area = padded_area(area);
     let width = area.width as usize;
-    let fill = should_fill();
+    let fill = config.fill;
+    let theme = &appearance.theme;
+    let layout = &appearance.layout;
     let shared = if fill {
         None
     } else {
-        Some(shared_widths(snapshot))
+        Some(shared_widths(snapshot, appearance))
     };
 
-    let lines = build_lines(snapshot, fill)
+    let lines = build_lines(snapshot, layout)
         .into_iter()
-        .map(|segments| render_line(Some(width), &segments, fill, shared))
+        .map(|cells| render_line(Some(width), &cells, fill, shared.as_deref(), appearance))
         .collect::<Vec<_>>();
 
     let total_lines = lines.len().min(area.height as usize);
@@ -54,84 +106,67 @@ pub fn render(frame: &mut Frame<'_>, snapshot: &Snapshot) {
         .constraints(constraints)
         .split(area);
 
-    let base_style = Style::default().bg(ROW_BG).fg(ROW_FG);
+    let base_style = Style::default().bg(theme.row_bg).fg(theme.row_fg);
     for (idx, line) in lines.into_iter().take(total_lines).enumerate() {
         frame.render_widget(Paragraph::new(line).style(base_style), rows[idx]);
     }
 }
 
-pub fn format_output(snapshot: &Snapshot) -> String {
-    let width = terminal_width();
-    let fill = should_fill();
+pub fn format_output(snapshot: &Snapshot, config: &Config, appearance: &Appearance) -> String {
+    let width = terminal_width(config);
+    let fill = config.fill;
+    let layout = &appearance.layout;
     let shared = if fill {
         None
     } else {
-        Some(shared_widths(snapshot))
+        Some(shared_widths(snapshot, appearance))
     };
-    let lines = build_lines(snapshot, fill)
+    let lines = build_lines(snapshot, layout)
         .into_iter()
-        .map(|segments| format_row(&segments, width, fill, shared))
+        .map(|cells| format_row(&cells, width, fill, shared.as_deref(), appearance))
         .collect::<Vec<_>>();
     format!("{}\n", lines.join("\n"))
 }
 
-fn build_lines<'a>(snapshot: &'a Snapshot, fill: bool) -> Vec<Vec<Segment<'a>>> {
-    // Row 1: Claude info
-    let row1 = [
-        snapshot.model.as_str(),
-        snapshot.version.as_str(),
-        snapshot.contributions.as_str(),
-        snapshot.session_clock.as_str(),
-    ];
-    // Row 2: Git info
-    let row2 = [
-        snapshot.repository.as_str(),
-        snapshot.branch.as_str(),
-        snapshot.git_changes.as_str(),
-        snapshot.ahead_behind.as_str(),
-    ];
-    // Row 3: Context info
-    let row3 = [
-        snapshot.context.as_str(),
-        snapshot.context_remaining.as_str(),
-        "",
-        snapshot.now_clock.as_str(),
-    ];
-
-    let mut lines = Vec::new();
-    lines.extend(split_segments(row1, fill));
-    lines.extend(split_segments(row2, fill));
-    lines.extend(split_segments(row3, fill));
-    lines
-}
-
-fn split_segments<'a>(row: [&'a str; 4], fill: bool) -> Vec<Vec<Segment<'a>>> {
-    let segments: Vec<Segment<'a>> = row
+fn build_lines<'a>(snapshot: &'a Snapshot, layout: &'a StatusLayout) -> Vec<Vec<Cell<'a>>> {
+    layout
+        .rows
         .iter()
-        .enumerate()
-        .map(|(idx, value)| (idx, *value))
-        .collect();
-
-    if fill {
-        return vec![segments];
-    }
-
-    vec![segments]
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(column, cell)| Cell {
+                    column,
+                    value: snapshot.get(&cell.id).unwrap_or("-"),
+                    tone: snapshot.tone(&cell.id),
+                    shorter: snapshot.shorter(&cell.id),
+                    layout: cell,
+                })
+                .collect()
+        })
+        .collect()
 }
 
 fn format_row(
-    segments: &[Segment<'_>],
+    cells: &[Cell<'_>],
     width_opt: Option<usize>,
     fill: bool,
-    shared_widths: Option<[usize; 4]>,
+    shared_widths: Option<&[usize]>,
+    appearance: &Appearance,
 ) -> String {
-    let widths = if fill {
-        width_opt.map(|w| column_widths(w.saturating_sub(LINE_PREFIX.len())))
+    let theme = &appearance.theme;
+    let fill_widths = if fill {
+        width_opt.map(|w| {
+            appearance
+                .layout
+                .column_widths(w.saturating_sub(LINE_PREFIX.len()))
+        })
     } else {
-        shared_widths
+        None
     };
+    let widths = fill_widths.as_deref().or(shared_widths);
     let mut out = String::new();
-    let row_style = ansi_fg_bg(ROW_FG, ROW_BG);
+    let row_style = ansi_fg_bg(theme.row_fg, theme.row_bg, appearance.depth);
     out.push_str(&row_style);
     out.push_str(LINE_PREFIX);
 
@@ -139,17 +174,17 @@ fn format_row(
         .map(|w| w.saturating_sub(LINE_PREFIX.len()))
         .unwrap_or(usize::MAX);
 
-    for (idx, value) in segments.iter() {
+    for cell in cells {
         let width = match widths {
             Some(cols) => {
-                let mut w = cols[*idx];
+                let mut w = cols[cell.column];
                 if width_opt.is_some() {
                     w = w.min(remaining);
                 }
                 w
             }
             None => {
-                let natural = natural_width(value, *idx);
+                let natural = natural_width(cell.value, cell.layout.style, appearance);
                 if width_opt.is_some() {
                     natural.min(remaining)
                 } else {
@@ -160,11 +195,13 @@ fn format_row(
         if width == 0 {
             break;
         }
-        let segment = match *idx {
-            0 => ansi_pill(value, width, SAKURA, SAKURA_FG),
-            3 => ansi_pill(value, width, GREEN, GREEN_FG),
-            2 => ansi_git_changes(value, width, MID_BG, MID_FG),
-            _ => ansi_block(value, width, MID_BG, MID_FG),
+        let cell = &cell.fitted(width, appearance);
+        let (bg, fg) = cell_colors(cell, theme);
+        let segment = match cell.layout.style {
+            CellStyle::Pill => ansi_pill(cell.value, width, bg, fg, appearance),
+            CellStyle::Diff => ansi_git_changes(cell.value, width, bg, fg, appearance),
+            CellStyle::Block => ansi_block(cell.value, width, bg, fg, appearance),
+            CellStyle::Gauge => ansi_gauge(cell, width, bg, fg, appearance),
         };
         out.push_str(&segment);
 
@@ -176,7 +213,9 @@ fn format_row(
         }
     }
 
-    out.push_str("\x1b[0m");
+    if appearance.depth != ColorDepth::NoColor {
+        out.push_str("\x1b[0m");
+    }
     out
 }
 
@@ -192,22 +231,25 @@ fn padded_area(area: Rect) -> Rect {
     }
 }
 
-fn column_widths(total_width: usize) -> [usize; 4] {
-    let usable = total_width;
-    let mut widths = [0usize; 4];
-    let mut used = 0usize;
-
-    for (idx, pct) in COL_PCTS.iter().enumerate() {
-        let w = usable * (*pct as usize) / 100;
-        widths[idx] = w;
-        used += w;
+/// A pill's tone replaces its background; a block's, its text color.
+fn cell_colors(cell: &Cell<'_>, theme: &Theme) -> (Color, Color) {
+    let tone = tone_color(cell.tone, theme);
+    match (cell.layout.style, cell.layout.accent) {
+        (CellStyle::Pill, Accent::Primary) => (tone.unwrap_or(theme.primary), theme.primary_fg),
+        (CellStyle::Pill, Accent::Secondary) => {
+            (tone.unwrap_or(theme.secondary), theme.secondary_fg)
+        }
+        _ => (theme.mid_bg, tone.unwrap_or(theme.mid_fg)),
     }
+}
 
-    if used < usable {
-        widths[3] += usable - used;
+fn tone_color(tone: Tone, theme: &Theme) -> Option<Color> {
+    match tone {
+        Tone::Normal => None,
+        Tone::Calm => Some(theme.calm),
+        Tone::Warn => Some(theme.warn),
+        Tone::Danger => Some(theme.danger),
     }
-
-    widths
 }
 
 fn fit_cell(text: &str, width: usize) -> String {
@@ -228,37 +270,44 @@ fn segment_text(value: &str) -> String {
 
 fn render_line(
     width_opt: Option<usize>,
-    segments: &[Segment<'_>],
+    cells: &[Cell<'_>],
     fill: bool,
-    shared_widths: Option<[usize; 4]>,
+    shared_widths: Option<&[usize]>,
+    appearance: &Appearance,
 ) -> Line<'static> {
-    let widths = if fill {
-        width_opt.map(|w| column_widths(w.saturating_sub(LINE_PREFIX.len())))
+    let theme = &appearance.theme;
+    let fill_widths = if fill {
+        width_opt.map(|w| {
+            appearance
+                .layout
+                .column_widths(w.saturating_sub(LINE_PREFIX.len()))
+        })
     } else {
-        shared_widths
+        None
     };
+    let widths = fill_widths.as_deref().or(shared_widths);
     let mut spans = Vec::with_capacity(10);
 
     spans.push(Span::styled(
         LINE_PREFIX,
-        Style::default().bg(ROW_BG).fg(ROW_FG),
+        Style::default().bg(theme.row_bg).fg(theme.row_fg),
     ));
 
     let mut remaining = width_opt
         .map(|w| w.saturating_sub(LINE_PREFIX.len()))
         .unwrap_or(usize::MAX);
 
-    for (idx, value) in segments.iter() {
+    for cell in cells {
         let width = match widths {
             Some(cols) => {
-                let mut w = cols[*idx];
+                let mut w = cols[cell.column];
                 if width_opt.is_some() {
                     w = w.min(remaining);
                 }
                 w
             }
             None => {
-                let natural = natural_width(value, *idx);
+                let natural = natural_width(cell.value, cell.layout.style, appearance);
                 if width_opt.is_some() {
                     natural.min(remaining)
                 } else {
@@ -269,19 +318,14 @@ fn render_line(
         if width == 0 {
             break;
         }
-
-        let (bg, fg) = match *idx {
-            0 => (SAKURA, SAKURA_FG),
-            3 => (GREEN, GREEN_FG),
-            _ => (MID_BG, MID_FG),
-        };
-
-        let segment = if *idx == 0 || *idx == 3 {
-            pill_spans(value, width, bg, fg)
-        } else if *idx == 2 {
-            git_changes_spans(value, width, bg, fg)
-        } else {
-            block_spans(value, width, bg, fg)
+        let cell = &cell.fitted(width, appearance);
+
+        let (bg, fg) = cell_colors(cell, theme);
+        let segment = match cell.layout.style {
+            CellStyle::Pill => pill_spans(cell.value, width, bg, fg, appearance),
+            CellStyle::Diff => git_changes_spans(cell.value, width, bg, fg, theme),
+            CellStyle::Block => block_spans(cell.value, width, bg, fg),
+            CellStyle::Gauge => gauge_spans(cell, width, bg, fg, appearance),
         };
         spans.extend(segment);
 
@@ -296,52 +340,40 @@ fn render_line(
     Line::from(spans)
 }
 
-fn shared_widths(snapshot: &Snapshot) -> [usize; 4] {
-    let row1 = [
-        snapshot.model.as_str(),
-        snapshot.version.as_str(),
-        snapshot.contributions.as_str(),
-        snapshot.session_clock.as_str(),
-    ];
-    let row2 = [
-        snapshot.repository.as_str(),
-        snapshot.branch.as_str(),
-        snapshot.git_changes.as_str(),
-        snapshot.ahead_behind.as_str(),
-    ];
-    let row3 = [
-        snapshot.context.as_str(),
-        snapshot.context_remaining.as_str(),
-        "",
-        snapshot.now_clock.as_str(),
-    ];
-
-    let mut widths = [0usize; 4];
-    for (idx, value) in row1.iter().enumerate() {
-        widths[idx] = widths[idx].max(natural_width(value, idx));
-    }
-    for (idx, value) in row2.iter().enumerate() {
-        widths[idx] = widths[idx].max(natural_width(value, idx));
-    }
-    for (idx, value) in row3.iter().enumerate() {
-        widths[idx] = widths[idx].max(natural_width(value, idx));
+fn shared_widths(snapshot: &Snapshot, appearance: &Appearance) -> Vec<usize> {
+    let layout = &appearance.layout;
+    let mut widths = vec![0usize; layout.column_count()];
+    for row in build_lines(snapshot, layout) {
+        for cell in row {
+            widths[cell.column] =
+                widths[cell.column].max(natural_width(cell.value, cell.layout.style, appearance));
+        }
     }
     widths
 }
 
-fn pill_spans(value: &str, width: usize, bg: Color, fg: Color) -> Vec<Span<'static>> {
+fn pill_spans(
+    value: &str,
+    width: usize,
+    bg: Color,
+    fg: Color,
+    appearance: &Appearance,
+) -> Vec<Span<'static>> {
+    let theme = &appearance.theme;
     let mut spans = Vec::new();
-    if width < PILL_BORDER_WIDTH {
+    let border = appearance.glyphs.width();
+    if width < border {
         return block_spans(value, width, bg, fg);
     }
-    let inner_width = width.saturating_sub(PILL_BORDER_WIDTH);
+    let inner_width = width.saturating_sub(border);
     let inner = fit_cell(&segment_text(value), inner_width);
 
-    spans.push(Span::styled(ROUND_LEFT, Style::default().fg(bg).bg(ROW_BG)));
+    let (left, right) = pill_caps(appearance);
+    spans.push(Span::styled(left, Style::default().fg(bg).bg(theme.row_bg)));
     spans.push(Span::styled(inner, Style::default().fg(fg).bg(bg)));
     spans.push(Span::styled(
-        ROUND_RIGHT,
-        Style::default().fg(bg).bg(ROW_BG),
+        right,
+        Style::default().fg(bg).bg(theme.row_bg),
     ));
 
     spans
@@ -354,7 +386,13 @@ fn block_spans(value: &str, width: usize, bg: Color, fg: Color) -> Vec<Span<'sta
     spans
 }
 
-fn git_changes_spans(value: &str, width: usize, bg: Color, fg: Color) -> Vec<Span<'static>> {
+fn git_changes_spans(
+    value: &str,
+    width: usize,
+    bg: Color,
+    fg: Color,
+    theme: &Theme,
+) -> Vec<Span<'static>> {
     if width == 0 {
         return Vec::new();
     }
@@ -365,7 +403,9 @@ fn git_changes_spans(value: &str, width: usize, bg: Color, fg: Color) -> Vec<Spa
     let mut chars = text.chars().peekable();
 
     while let Some(ch) = chars.next() {
-        if (ch == '+' || ch == '-') && chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+        let color =
+            diff_color(ch, theme).filter(|_| chars.peek().is_some_and(char::is_ascii_digit));
+        if let Some(color) = color {
             if !buffer.is_empty() {
                 spans.push(Span::styled(buffer.clone(), Style::default().fg(fg).bg(bg)));
                 buffer.clear();
@@ -382,7 +422,6 @@ fn git_changes_spans(value: &str, width: usize, bg: Color, fg: Color) -> Vec<Spa
                 }
             }
 
-            let color = if ch == '+' { PLUS_FG } else { MINUS_FG };
             spans.push(Span::styled(token, Style::default().fg(color).bg(bg)));
         } else {
             buffer.push(ch);
@@ -396,49 +435,65 @@ fn git_changes_spans(value: &str, width: usize, bg: Color, fg: Color) -> Vec<Spa
     spans
 }
 
-fn ansi_pill(value: &str, width: usize, bg: Color, fg: Color) -> String {
-    if width < PILL_BORDER_WIDTH {
-        return ansi_block(value, width, bg, fg);
+fn ansi_pill(value: &str, width: usize, bg: Color, fg: Color, appearance: &Appearance) -> String {
+    let border = appearance.glyphs.width();
+    if width < border {
+        return ansi_block(value, width, bg, fg, appearance);
     }
-    let inner_width = width.saturating_sub(PILL_BORDER_WIDTH);
+    let theme = &appearance.theme;
+    let depth = appearance.depth;
+    let inner_width = width.saturating_sub(border);
     let inner = fit_cell(&segment_text(value), inner_width);
+    let (left, right) = pill_caps(appearance);
     let mut out = String::new();
 
-    out.push_str(&ansi_fg_bg_color(bg, ROW_BG));
-    out.push_str(ROUND_LEFT);
-    out.push_str(&ansi_fg_bg_color(fg, bg));
+    out.push_str(&ansi_fg_bg(bg, theme.row_bg, depth));
+    out.push_str(left);
+    out.push_str(&ansi_fg_bg(fg, bg, depth));
     out.push_str(&inner);
-    out.push_str(&ansi_fg_bg_color(bg, ROW_BG));
-    out.push_str(ROUND_RIGHT);
-    out.push_str(&ansi_fg_bg(ROW_FG, ROW_BG));
+    out.push_str(&ansi_fg_bg(bg, theme.row_bg, depth));
+    out.push_str(right);
+    out.push_str(&ansi_fg_bg(theme.row_fg, theme.row_bg, depth));
     out
 }
 
-fn ansi_block(value: &str, width: usize, bg: Color, fg: Color) -> String {
+fn ansi_block(value: &str, width: usize, bg: Color, fg: Color, appearance: &Appearance) -> String {
     if width == 0 {
         return String::new();
     }
 
+    let theme = &appearance.theme;
+    let depth = appearance.depth;
     let text = fit_cell(&segment_text(value), width);
     let mut out = String::new();
-    out.push_str(&ansi_fg_bg_color(fg, bg));
+    out.push_str(&ansi_fg_bg(fg, bg, depth));
     out.push_str(&text);
-    out.push_str(&ansi_fg_bg(ROW_FG, ROW_BG));
+    out.push_str(&ansi_fg_bg(theme.row_fg, theme.row_bg, depth));
     out
 }
 
-fn ansi_git_changes(value: &str, width: usize, bg: Color, fg: Color) -> String {
+fn ansi_git_changes(
+    value: &str,
+    width: usize,
+    bg: Color,
+    fg: Color,
+    appearance: &Appearance,
+) -> String {
     if width == 0 {
         return String::new();
     }
 
+    let theme = &appearance.theme;
+    let depth = appearance.depth;
     let text = fit_cell(&segment_text(value), width);
     let mut out = String::new();
-    out.push_str(&ansi_fg_bg_color(fg, bg));
+    out.push_str(&ansi_fg_bg(fg, bg, depth));
 
     let mut chars = text.chars().peekable();
     while let Some(ch) = chars.next() {
-        if (ch == '+' || ch == '-') && chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+        let color =
+            diff_color(ch, theme).filter(|_| chars.peek().is_some_and(char::is_ascii_digit));
+        if let Some(color) = color {
             let mut token = String::new();
             token.push(ch);
             while let Some(next) = chars.peek() {
@@ -450,69 +505,139 @@ fn ansi_git_changes(value: &str, width: usize, bg: Color, fg: Color) -> String {
                 }
             }
 
-            let color = if ch == '+' { PLUS_FG } else { MINUS_FG };
-            out.push_str(&ansi_fg_bg_color(color, bg));
+            out.push_str(&ansi_fg_bg(color, bg, depth));
             out.push_str(&token);
-            out.push_str(&ansi_fg_bg_color(fg, bg));
+            out.push_str(&ansi_fg_bg(fg, bg, depth));
         } else {
             out.push(ch);
         }
     }
 
-    out.push_str(&ansi_fg_bg(ROW_FG, ROW_BG));
+    out.push_str(&ansi_fg_bg(theme.row_fg, theme.row_bg, depth));
     out
 }
 
-fn natural_width(value: &str, idx: usize) -> usize {
-    let base = display_width(&segment_text(value));
-    if idx == 0 || idx == 3 {
-        base + 2
-    } else {
-        base
+/// The color of a count in a diff cell, by the symbol before it: `+n` /
+/// `-m` line changes and the file kinds of `git_status`.
+fn diff_color(symbol: char, theme: &Theme) -> Option<Color> {
+    match symbol {
+        '+' | '●' => Some(theme.plus),
+        '-' | '✖' => Some(theme.minus),
+        '✚' => Some(theme.warn),
+        '»' => Some(theme.calm),
+        '=' => Some(theme.danger),
+        '…' => Some(theme.mid_fg),
+        _ => None,
     }
 }
 
-fn ansi_fg_bg(fg: Color, bg: Color) -> String {
-    ansi_fg_bg_color(fg, bg)
+/// A gauge cell as (text, fg, bg) runs: a bar filled to the value's leading
+/// percentage, then the value itself. `None` when there is no percentage or
+/// no room for a bar.
+fn gauge_runs(
+    cell: &Cell<'_>,
+    width: usize,
+    bg: Color,
+    fg: Color,
+    appearance: &Appearance,
+) -> Option<Vec<(String, Color, Color)>> {
+    let percent: f64 = cell.value.split('%').next()?.trim().parse().ok()?;
+    let label = segment_text(cell.value);
+    let bar_width = width.checked_sub(display_width(&label) + 1)?;
+    if bar_width < GAUGE_MIN_BAR {
+        return None;
+    }
+
+    let theme = &appearance.theme;
+    let eighths = (percent.clamp(0.0, 100.0) / 100.0 * (bar_width * 8) as f64).round() as usize;
+    let (full, part) = (eighths / 8, eighths % 8);
+    let mut bar = "█".repeat(full);
+    bar.push_str(GAUGE_EIGHTHS[part]);
+
+    // Without color the trough is invisible, so draw it.
+    let trough = if appearance.depth == ColorDepth::NoColor {
+        "░"
+    } else {
+        " "
+    };
+    let empty = trough.repeat(bar_width - full - usize::from(part > 0));
+    let fill = tone_color(cell.tone, theme).unwrap_or(theme.primary);
+
+    Some(vec![
+        (" ".to_string(), fg, bg),
+        (bar, fill, theme.row_bg),
+        (empty, fg, theme.row_bg),
+        (label, fg, bg),
+    ])
 }
 
-fn ansi_fg_bg_color(fg: Color, bg: Color) -> String {
-    let (fr, fg_c, fb) = rgb(fg);
-    let (br, bg_c, bb) = rgb(bg);
-    format!(
-        "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m",
-        fr, fg_c, fb, br, bg_c, bb
-    )
+fn gauge_spans(
+    cell: &Cell<'_>,
+    width: usize,
+    bg: Color,
+    fg: Color,
+    appearance: &Appearance,
+) -> Vec<Span<'static>> {
+    match gauge_runs(cell, width, bg, fg, appearance) {
+        Some(runs) => runs
+            .into_iter()
+            .map(|(text, fg, bg)| Span::styled(text, Style::default().fg(fg).bg(bg)))
+            .collect(),
+        None => block_spans(cell.value, width, bg, fg),
+    }
 }
 
-fn rgb(color: Color) -> (u8, u8, u8) {
-    match color {
-        Color::Rgb(r, g, b) => (r, g, b),
-        _ => (255, 255, 255),
+fn ansi_gauge(
+    cell: &Cell<'_>,
+    width: usize,
+    bg: Color,
+    fg: Color,
+    appearance: &Appearance,
+) -> String {
+    let Some(runs) = gauge_runs(cell, width, bg, fg, appearance) else {
+        return ansi_block(cell.value, width, bg, fg, appearance);
+    };
+
+    let theme = &appearance.theme;
+    let depth = appearance.depth;
+    let mut out = String::new();
+    for (text, fg, bg) in runs {
+        out.push_str(&ansi_fg_bg(fg, bg, depth));
+        out.push_str(&text);
     }
+    out.push_str(&ansi_fg_bg(theme.row_fg, theme.row_bg, depth));
+    out
 }
 
-fn should_fill() -> bool {
-    match std::env::var("CC_STATUSLINE_FILL") {
-        Ok(val) => matches!(val.as_str(), "1" | "true" | "yes" | "on"),
-        Err(_) => false,
+fn natural_width(value: &str, style: CellStyle, appearance: &Appearance) -> usize {
+    let base = display_width(&segment_text(value));
+    match style {
+        CellStyle::Pill => base + appearance.glyphs.width(),
+        CellStyle::Gauge => base + 1 + GAUGE_BAR,
+        _ => base,
     }
 }
 
-fn terminal_width() -> Option<usize> {
-    if let Ok(val) = std::env::var("CC_STATUSLINE_WIDTH") {
-        if let Ok(width) = val.parse::<usize>() {
-            return Some(apply_width_adjustments(width));
-        }
+fn pill_caps(appearance: &Appearance) -> (&'static str, &'static str) {
+    appearance
+        .glyphs
+        .caps(appearance.depth != ColorDepth::NoColor)
+}
+
+fn terminal_width(config: &Config) -> Option<usize> {
+    let adjust = |width: usize| width.saturating_sub(config.reserved);
+
+    if let Some(width) = config.width {
+        return Some(adjust(width));
     }
 
     if let Ok(val) = std::env::var("COLUMNS") {
         if let Ok(width) = val.parse::<usize>() {
-            return Some(apply_width_adjustments(width));
+            return Some(adjust(width));
         }
     }
 
-    tput_cols().map(apply_width_adjustments)
+    tput_cols().map(adjust)
 }
 
 fn display_width(text: &str) -> usize {
@@ -547,15 +672,6 @@ fn tput_cols() -> Option<usize> {
     None
 }
 
-fn apply_width_adjustments(width: usize) -> usize {
-    let reserved = std::env::var("CC_STATUSLINE_RESERVED")
-        .ok()
-        .and_then(|v| v.parse::<usize>().ok())
-        .unwrap_or(0);
-
-    width.saturating_sub(reserved)
-}
-
 fn run_cols_cmd(cmd: &str, args: &[&str], tty: Option<&File>) -> Option<usize> {
     let mut command = Command::new(cmd);
     command.args(args);
@@ -591,30 +707,118 @@ fn stty_cols(tty: Option<&File>) -> Option<usize> {
 
 #[cfg(test)]
 mod tests {
-    use super::format_output;
-    use crate::data::Snapshot;
+    use super::{
+        cell_colors, display_width, format_output, gauge_runs, Appearance, Cell, ColorDepth,
+        LayoutCell,
+    };
+    use crate::config::{Accent, CellStyle, Config};
+    use crate::segment::{Snapshot, Tone};
+    use ratatui::style::Color;
+
+    fn sample_snapshot() -> Snapshot {
+        Snapshot::from_iter([
+            ("model", "model"),
+            ("version", "0.1.0"),
+            ("contributions", "🌲 9"),
+            ("session", "5h32m"),
+            ("repository", "owner/repo"),
+            ("branch", "main"),
+            ("git_changes", "+3 -1"),
+            ("ahead_behind", "↑1 ↓0"),
+            ("context", "10K/100K"),
+            ("context_remaining", "90% left"),
+            ("clock", "12:34:56"),
+            ("empty", ""),
+        ])
+    }
 
     #[test]
     fn format_output_contains_lines() {
-        let snapshot = Snapshot {
-            model: "model".to_string(),
-            version: "0.1.0".to_string(),
-            contributions: "🌲 9".to_string(),
-            session_clock: "5h32m".to_string(),
-            repository: "owner/repo".to_string(),
-            branch: "main".to_string(),
-            git_changes: "+3 -1".to_string(),
-            ahead_behind: "↑1 ↓0".to_string(),
-            context: "10K/100K".to_string(),
-            context_remaining: "90% left".to_string(),
-            now_clock: "12:34:56".to_string(),
-        };
+        let snapshot = sample_snapshot();
 
-        let output = format_output(&snapshot);
+        let output = format_output(&snapshot, &Config::default(), &Appearance::default());
         let lines: Vec<&str> = output.trim_end().split('\n').collect();
         assert!(lines.len() >= 3);
         assert!(output.contains("model"));
         assert!(output.contains("0.1.0"));
         assert!(output.contains("owner/repo"));
     }
+
+    #[test]
+    fn no_color_output_is_plain_and_aligned() {
+        let appearance = Appearance {
+            depth: ColorDepth::NoColor,
+            ..Appearance::default()
+        };
+        let output = format_output(&sample_snapshot(), &Config::default(), &appearance);
+
+        assert!(!output.contains('\x1b'));
+        let widths: Vec<usize> = output.lines().map(display_width).collect();
+        assert!(widths.iter().all(|w| *w == widths[0]));
+    }
+
+    #[test]
+    fn tone_tints_pills_and_blocks() {
+        let theme = Appearance::default().theme;
+        let colors = |style, tone| {
+            let layout = LayoutCell {
+                id: "ahead_behind".to_string(),
+                style,
+                accent: Accent::Secondary,
+            };
+            let cell = Cell {
+                column: 0,
+                value: "gone",
+                tone,
+                shorter: &[],
+                layout: &layout,
+            };
+            cell_colors(&cell, &theme)
+        };
+
+        assert_eq!(
+            colors(CellStyle::Pill, Tone::Normal),
+            (theme.secondary, theme.secondary_fg)
+        );
+        assert_eq!(
+            colors(CellStyle::Pill, Tone::Warn),
+            (theme.warn, theme.secondary_fg)
+        );
+        assert_eq!(
+            colors(CellStyle::Block, Tone::Warn),
+            (theme.mid_bg, theme.warn)
+        );
+    }
+
+    #[test]
+    fn gauge_fills_to_percentage() {
+        let appearance = Appearance {
+            depth: ColorDepth::NoColor,
+            ..Appearance::default()
+        };
+        let layout = LayoutCell {
+            id: "context_gauge".to_string(),
+            style: CellStyle::Gauge,
+            accent: Accent::Primary,
+        };
+        let cell = |value| Cell {
+            column: 0,
+            value,
+            tone: Tone::Warn,
+            shorter: &[],
+            layout: &layout,
+        };
+        let bar = |value| {
+            gauge_runs(&cell(value), 16, Color::Reset, Color::Reset, &appearance).map(|runs| {
+                runs.into_iter()
+                    .map(|(text, _, _)| text)
+                    .collect::<String>()
+            })
+        };
+
+        assert_eq!(bar("50%").as_deref(), Some(" █████░░░░░ 50% "));
+        assert_eq!(bar("0%").as_deref(), Some(" ░░░░░░░░░░░ 0% "));
+        assert_eq!(bar("-"), None);
+        assert_eq!(bar("100% and then some"), None);
+    }
 }
@@ -0,0 +1,238 @@
+use anyhow::{anyhow, Context, Result};
+use ratatui::style::Color;
+use std::str::FromStr;
+
+use crate::config::{Config, ThemeConfig};
+use crate::ui::color::ColorDepth;
+
+const DEFAULT_THEME: &str = "sakura";
+
+/// Every color role used by both the ratatui preview and the ANSI output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub primary: Color,
+    pub primary_fg: Color,
+    pub secondary: Color,
+    pub secondary_fg: Color,
+    pub row_bg: Color,
+    pub row_fg: Color,
+    pub mid_bg: Color,
+    pub mid_fg: Color,
+    pub plus: Color,
+    pub minus: Color,
+    /// Gauge fill by level.
+    pub calm: Color,
+    pub warn: Color,
+    pub danger: Color,
+}
+
+impl Theme {
+    pub fn sakura() -> Self {
+        Self {
+            primary: Color::Rgb(241, 157, 181),
+            primary_fg: Color::Rgb(35, 30, 30),
+            secondary: Color::Rgb(154, 199, 122),
+            secondary_fg: Color::Rgb(30, 45, 28),
+            row_bg: Color::Rgb(40, 40, 40),
+            row_fg: Color::Rgb(220, 220, 220),
+            mid_bg: Color::Rgb(55, 55, 55),
+            mid_fg: Color::Rgb(220, 220, 220),
+            plus: Color::Rgb(98, 201, 98),
+            minus: Color::Rgb(235, 110, 110),
+            calm: Color::Rgb(154, 199, 122),
+            warn: Color::Rgb(240, 190, 100),
+            danger: Color::Rgb(235, 110, 110),
+        }
+    }
+
+    /// Night blossoms: deep indigo rows with lantern-lit accents.
+    pub fn yozakura() -> Self {
+        Self {
+            primary: Color::Rgb(232, 136, 170),
+            primary_fg: Color::Rgb(22, 16, 30),
+            secondary: Color::Rgb(233, 196, 128),
+            secondary_fg: Color::Rgb(38, 28, 14),
+            row_bg: Color::Rgb(18, 18, 30),
+            row_fg: Color::Rgb(205, 205, 225),
+            mid_bg: Color::Rgb(34, 34, 54),
+            mid_fg: Color::Rgb(205, 205, 225),
+            plus: Color::Rgb(120, 200, 150),
+            minus: Color::Rgb(240, 120, 140),
+            calm: Color::Rgb(120, 200, 150),
+            warn: Color::Rgb(233, 196, 128),
+            danger: Color::Rgb(240, 120, 140),
+        }
+    }
+
+    /// For light terminal backgrounds.
+    pub fn sakura_light() -> Self {
+        Self {
+            primary: Color::Rgb(214, 92, 130),
+            primary_fg: Color::Rgb(255, 250, 252),
+            secondary: Color::Rgb(92, 150, 70),
+            secondary_fg: Color::Rgb(250, 255, 248),
+            row_bg: Color::Rgb(250, 244, 246),
+            row_fg: Color::Rgb(60, 50, 55),
+            mid_bg: Color::Rgb(236, 224, 229),
+            mid_fg: Color::Rgb(60, 50, 55),
+            plus: Color::Rgb(30, 140, 50),
+            minus: Color::Rgb(200, 50, 60),
+            calm: Color::Rgb(30, 140, 50),
+            warn: Color::Rgb(190, 120, 0),
+            danger: Color::Rgb(200, 50, 60),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            primary: Color::Rgb(255, 110, 180),
+            primary_fg: Color::Rgb(0, 0, 0),
+            secondary: Color::Rgb(0, 230, 118),
+            secondary_fg: Color::Rgb(0, 0, 0),
+            row_bg: Color::Rgb(0, 0, 0),
+            row_fg: Color::Rgb(255, 255, 255),
+            mid_bg: Color::Rgb(48, 48, 48),
+            mid_fg: Color::Rgb(255, 255, 255),
+            plus: Color::Rgb(0, 255, 0),
+            minus: Color::Rgb(255, 64, 64),
+            calm: Color::Rgb(0, 255, 0),
+            warn: Color::Rgb(255, 215, 0),
+            danger: Color::Rgb(255, 64, 64),
+        }
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "sakura" => Some(Self::sakura()),
+            "yozakura" => Some(Self::yozakura()),
+            "sakura-light" => Some(Self::sakura_light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            _ => None,
+        }
+    }
+
+    /// Resolve the configured theme name; `[themes.<name>]` tables take
+    /// precedence over built-ins and start from their `base` theme.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let name = config.theme.as_deref().unwrap_or(DEFAULT_THEME);
+
+        let Some(custom) = config.themes.get(name) else {
+            return Self::builtin(name).ok_or_else(|| anyhow!("unknown theme `{}`", name));
+        };
+
+        let base = custom.base.as_deref().unwrap_or(DEFAULT_THEME);
+        let mut theme = Self::builtin(base)
+            .ok_or_else(|| anyhow!("theme `{}` has unknown base `{}`", name, base))?;
+        theme
+            .apply(custom)
+            .with_context(|| format!("invalid theme `{}`", name))?;
+        Ok(theme)
+    }
+
+    /// The same theme with every role mapped into `depth`.
+    pub fn adapted(&self, depth: ColorDepth) -> Self {
+        let adapt = |color| depth.adapt(color);
+        Self {
+            primary: adapt(self.primary),
+            primary_fg: adapt(self.primary_fg),
+            secondary: adapt(self.secondary),
+            secondary_fg: adapt(self.secondary_fg),
+            row_bg: adapt(self.row_bg),
+            row_fg: adapt(self.row_fg),
+            mid_bg: adapt(self.mid_bg),
+            mid_fg: adapt(self.mid_fg),
+            plus: adapt(self.plus),
+            minus: adapt(self.minus),
+            calm: adapt(self.calm),
+            warn: adapt(self.warn),
+            danger: adapt(self.danger),
+        }
+    }
+
+    fn apply(&mut self, custom: &ThemeConfig) -> Result<()> {
+        let roles = [
+            (&mut self.primary, &custom.primary, "primary"),
+            (&mut self.primary_fg, &custom.primary_fg, "primary_fg"),
+            (&mut self.secondary, &custom.secondary, "secondary"),
+            (&mut self.secondary_fg, &custom.secondary_fg, "secondary_fg"),
+            (&mut self.row_bg, &custom.row_bg, "row_bg"),
+            (&mut self.row_fg, &custom.row_fg, "row_fg"),
+            (&mut self.mid_bg, &custom.mid_bg, "mid_bg"),
+            (&mut self.mid_fg, &custom.mid_fg, "mid_fg"),
+            (&mut self.plus, &custom.plus, "plus"),
+            (&mut self.minus, &custom.minus, "minus"),
+            (&mut self.calm, &custom.calm, "calm"),
+            (&mut self.warn, &custom.warn, "warn"),
+            (&mut self.danger, &custom.danger, "danger"),
+        ];
+
+        for (slot, value, role) in roles {
+            if let Some(value) = value {
+                *slot = Color::from_str(value)
+                    .map_err(|_| anyhow!("`{}` is not a color for `{}`", value, role))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::sakura()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Theme;
+    use crate::config::{Config, ThemeConfig};
+    use ratatui::style::Color;
+
+    fn config_with(theme: &str, custom: Option<ThemeConfig>) -> Config {
+        let mut config = Config {
+            theme: Some(theme.to_string()),
+            ..Config::default()
+        };
+        if let Some(custom) = custom {
+            config.themes.insert(theme.to_string(), custom);
+        }
+        config
+    }
+
+    #[test]
+    fn defaults_to_sakura() {
+        assert_eq!(
+            Theme::from_config(&Config::default()).unwrap(),
+            Theme::sakura()
+        );
+    }
+
+    #[test]
+    fn resolves_builtin_by_name() {
+        let theme = Theme::from_config(&config_with("yozakura", None)).unwrap();
+        assert_eq!(theme, Theme::yozakura());
+    }
+
+    #[test]
+    fn custom_theme_overrides_base() {
+        let custom = ThemeConfig {
+            base: Some("high-contrast".to_string()),
+            primary: Some("#102030".to_string()),
+            ..ThemeConfig::default()
+        };
+        let theme = Theme::from_config(&config_with("mine", Some(custom))).unwrap();
+        assert_eq!(theme.primary, Color::Rgb(16, 32, 48));
+        assert_eq!(theme.row_bg, Theme::high_contrast().row_bg);
+    }
+
+    #[test]
+    fn rejects_unknown_names_and_colors() {
+        assert!(Theme::from_config(&config_with("nope", None)).is_err());
+
+        let custom = ThemeConfig {
+            minus: Some("not-a-color".to_string()),
+            ..ThemeConfig::default()
+        };
+        assert!(Theme::from_config(&config_with("mine", Some(custom))).is_err());
+    }
+}